@@ -0,0 +1,19 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use perft::Position;
+
+// any string either parses or is reported as an error, and the FEN
+// written for a parsed position reads back as the same position
+fuzz_target!(|data: &[u8]| {
+    let Ok(fen) = std::str::from_utf8(data) else {
+        return;
+    };
+
+    if let Ok(pos) = Position::from_fen(fen) {
+        let written = pos.to_fen();
+        let again = Position::from_fen(&written).expect("written fen should parse");
+        assert_eq!(again.to_fen(), written);
+        assert_eq!(again.hash(), pos.hash());
+    }
+});
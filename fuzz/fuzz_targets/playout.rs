@@ -0,0 +1,70 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use perft::{Piece, Position, Side};
+
+const SUITES: [&str; 2] = [
+    include_str!("../../perft_results.txt"),
+    include_str!("../../perft_960.txt"),
+];
+
+// input with a newline starts from the FEN before it, anything else from
+// the suite position picked by its first two bytes, and each byte after
+// that picks one of the legal moves to play
+fuzz_target!(|data: &[u8]| {
+    let (mut pos, moves) = if let Some(split) = data.iter().position(|&byte| byte == b'\n') {
+        let Ok(fen) = std::str::from_utf8(&data[..split]) else {
+            return;
+        };
+        let Ok(pos) = Position::from_fen(fen) else {
+            return;
+        };
+        check(&pos);
+        (pos, &data[split + 1..])
+    } else {
+        let [hi, lo, moves @ ..] = data else {
+            return;
+        };
+
+        let fens = SUITES
+            .iter()
+            .flat_map(|suite| suite.lines())
+            .map(|line| line.split(';').next().unwrap_or_default())
+            .collect::<Vec<_>>();
+        let idx = usize::from(u16::from_be_bytes([*hi, *lo])) % fens.len();
+        (Position::parse_fen(fens[idx]), moves)
+    };
+
+    for &byte in moves {
+        let list = pos.gen();
+        assert!(list.len <= 252);
+        if list.len == 0 {
+            return;
+        }
+
+        pos.make(list.list[usize::from(byte) % list.len]);
+        check(&pos);
+    }
+});
+
+fn check(pos: &Position) {
+    // the side that just moved can't have left its king attacked
+    let moved = pos.stm() ^ 1;
+    let king = (pos.piece(moved) & pos.piece(Piece::KING)).trailing_zeros() as usize;
+    assert!(!pos.is_square_attacked(king, moved, pos.occ()), "{pos}");
+
+    let mut pieces = 0;
+    for piece in Piece::PAWN..=Piece::KING {
+        assert_eq!(pieces & pos.piece(piece), 0, "{pos}");
+        pieces |= pos.piece(piece);
+    }
+    assert_eq!(pos.piece(Side::WHITE) & pos.piece(Side::BLACK), 0, "{pos}");
+    assert_eq!(pieces, pos.occ(), "{pos}");
+
+    for side in [Side::WHITE, Side::BLACK] {
+        let kings = pos.piece(side) & pos.piece(Piece::KING);
+        assert_eq!(kings.count_ones(), 1, "{pos}");
+    }
+
+    assert_eq!(pos.hash(), pos.compute_hash(), "{pos}");
+}
@@ -1,5 +1,4 @@
 // Macro for calculating tables (until const fn pointers are stable).
-#[macro_export]
 macro_rules! init {
     ($sq:ident, $($rest:tt)+) => {{
         let mut $sq = 0;
@@ -12,28 +11,115 @@ macro_rules! init {
     }};
 }
 
+#[cfg(feature = "magic")]
+mod magic;
+#[cfg(feature = "pext")]
+mod pext;
+#[cfg(any(feature = "pext", feature = "magic"))]
+mod rays;
+#[cfg(feature = "magic")]
+pub use magic::Magic;
+#[cfg(feature = "pext")]
+pub use pext::Pext;
+
+/// Rook and bishop attacks for a square, given the occupancy of the board.
+///
+/// [`Attacks`] uses `Pext` with the `pext` feature, `Magic` with the `magic`
+/// feature, or [`Classic`] otherwise.
+pub trait Sliders {
+    /// Name of the backend, as printed by benchmarks.
+    const NAME: &'static str;
+
+    /// Squares attacked by a rook on `sq`.
+    fn rook(sq: usize, occ: u64) -> u64;
+    /// Squares attacked by a bishop on `sq`.
+    fn bishop(sq: usize, occ: u64) -> u64;
+}
+
+#[cfg(feature = "pext")]
+type Backend = Pext;
+#[cfg(all(feature = "magic", not(feature = "pext")))]
+type Backend = Magic;
+#[cfg(not(any(feature = "pext", feature = "magic")))]
+type Backend = Classic;
+
+/// Attack bitboards for each piece type.
 pub struct Attacks;
 
 impl Attacks {
+    /// Name of the [`Sliders`] backend selected by cargo features.
+    pub const BACKEND: &'static str = Backend::NAME;
+
+    /// Squares attacked by a pawn of `side` on `sq`.
     #[inline]
+    #[must_use]
     pub fn pawn(sq: usize, side: usize) -> u64 {
         LOOKUP.pawn[side][sq]
     }
 
+    /// Squares attacked by a knight on `sq`.
     #[inline]
+    #[must_use]
     pub fn knight(sq: usize) -> u64 {
         LOOKUP.knight[sq]
     }
 
+    /// Squares attacked by a king on `sq`.
     #[inline]
+    #[must_use]
     pub fn king(sq: usize) -> u64 {
         LOOKUP.king[sq]
     }
 
+    /// Squares attacked by a bishop on `sq`, given the occupancy `occ`.
+    #[inline]
+    #[must_use]
+    pub fn bishop(sq: usize, occ: u64) -> u64 {
+        Backend::bishop(sq, occ)
+    }
+
+    /// Squares attacked by a rook on `sq`, given the occupancy `occ`.
+    #[inline]
+    #[must_use]
+    pub fn rook(sq: usize, occ: u64) -> u64 {
+        Backend::rook(sq, occ)
+    }
+
+    /// Squares attacked by a queen on `sq`, given the occupancy `occ`.
+    #[inline]
+    #[must_use]
+    pub fn queen(sq: usize, occ: u64) -> u64 {
+        Self::bishop(sq, occ) | Self::rook(sq, occ)
+    }
+
+    /// Squares a rook on `sq` would additionally attack if the first `blockers` in its way were removed.
+    #[inline]
+    #[must_use]
+    pub fn xray_rook(sq: usize, occ: u64, blockers: u64) -> u64 {
+        let attacks = Self::rook(sq, occ);
+        attacks ^ Self::rook(sq, occ ^ (attacks & blockers))
+    }
+
+    /// Squares a bishop on `sq` would additionally attack if the first `blockers` in its way were removed.
+    #[inline]
+    #[must_use]
+    pub fn xray_bishop(sq: usize, occ: u64, blockers: u64) -> u64 {
+        let attacks = Self::bishop(sq, occ);
+        attacks ^ Self::bishop(sq, occ ^ (attacks & blockers))
+    }
+}
+
+/// Hyperbola quintessence for bishops and shifted rank and file lookups
+/// for rooks, needing no special instructions.
+pub struct Classic;
+
+impl Sliders for Classic {
+    const NAME: &'static str = "classic";
+
     // hyperbola quintessence
     // this gets automatically vectorised when targeting avx or better
     #[inline]
-    pub fn bishop(sq: usize, occ: u64) -> u64 {
+    fn bishop(sq: usize, occ: u64) -> u64 {
         let mask = LOOKUP.bishop[sq];
 
         let mut diag = occ & mask.diag;
@@ -56,7 +142,7 @@ impl Attacks {
     // shifted lookup
     // files and ranks are mapped to 1st rank and looked up by occupancy
     #[inline]
-    pub fn rook(sq: usize, occ: u64) -> u64 {
+    fn rook(sq: usize, occ: u64) -> u64 {
         let flip = ((occ >> (sq & 7)) & File::A).wrapping_mul(DIAG);
         let file_sq = (flip >> 57) & 0x3F;
         let files = LOOKUP.file[sq][file_sq as usize];
@@ -66,23 +152,6 @@ impl Attacks {
 
         ranks | files
     }
-
-    #[inline]
-    pub fn queen(sq: usize, occ: u64) -> u64 {
-        Self::bishop(sq, occ) | Self::rook(sq, occ)
-    }
-
-    #[inline]
-    pub fn xray_rook(sq: usize, occ: u64, blockers: u64) -> u64 {
-        let attacks = Self::rook(sq, occ);
-        attacks ^ Self::rook(sq, occ ^ (attacks & blockers))
-    }
-
-    #[inline]
-    pub fn xray_bishop(sq: usize, occ: u64, blockers: u64) -> u64 {
-        let attacks = Self::bishop(sq, occ);
-        attacks ^ Self::bishop(sq, occ ^ (attacks & blockers))
-    }
 }
 
 struct File;
@@ -91,8 +160,8 @@ impl File {
     const H: u64 = Self::A << 7;
 }
 
-const EAST: [u64; 64] = init! {sq, (0xFF << (sq & 56)) ^ (1 << sq) ^ WEST[sq]};
-const WEST: [u64; 64] = init! {sq, (0xFF << (sq & 56)) & ((1 << sq) - 1)};
+const EAST: [u64; 64] = init! {sq, (0xFF << (sq & 0x38)) ^ (1 << sq) ^ WEST[sq]};
+const WEST: [u64; 64] = init! {sq, (0xFF << (sq & 0x38)) & ((1 << sq) - 1)};
 const DIAG: u64 = DIAGS[7];
 const DIAGS: [u64; 15] = [
     0x0100_0000_0000_0000,
@@ -172,7 +241,7 @@ const BISHOP: [Mask; 64] = init! {sq,
 
 const RANK_SHIFT: [usize; 64] = init! {sq, sq - (sq & 7) + 1};
 
-const RANK: [[u64; 64]; 64] = init! {sq,
+static RANK: [[u64; 64]; 64] = init! {sq,
     init! {occ, {
         let file = sq & 7;
         let mask = (occ << 1) as u64;
@@ -182,7 +251,7 @@ const RANK: [[u64; 64]; 64] = init! {sq,
     }}
 };
 
-const FILE: [[u64; 64]; 64] = init! {sq,
+static FILE: [[u64; 64]; 64] = init! {sq,
     init! {occ, (RANK[7 - sq / 8][occ].wrapping_mul(DIAG) & File::H) >> (7 - (sq & 7))}
 };
 
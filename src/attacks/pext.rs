@@ -0,0 +1,41 @@
+use super::rays::{entries, table, Entry, BISHOP_DIRS, BISHOP_SIZE, ROOK_DIRS, ROOK_SIZE};
+use super::Sliders;
+use std::arch::x86_64::_pext_u64;
+
+// rustdoc does not get the rustflags from .cargo/config.toml, and
+// only builds the crate to find the doc tests and documentation
+#[cfg(not(any(target_feature = "bmi2", doc, doctest)))]
+compile_error!(
+    "the `pext` feature needs BMI2, build with `-C target-cpu=native` or `-C target-feature=+bmi2`"
+);
+
+/// Attacks looked up by the occupancy of the squares that can block a slider,
+/// packed into an index with the BMI2 `pext` instruction.
+pub struct Pext;
+
+impl Sliders for Pext {
+    const NAME: &'static str = "pext";
+
+    #[inline]
+    fn rook(sq: usize, occ: u64) -> u64 {
+        let entry = &ROOK_ENTRIES[sq];
+        ROOK_TABLE[entry.offset + pext(occ, entry.mask)]
+    }
+
+    #[inline]
+    fn bishop(sq: usize, occ: u64) -> u64 {
+        let entry = &BISHOP_ENTRIES[sq];
+        BISHOP_TABLE[entry.offset + pext(occ, entry.mask)]
+    }
+}
+
+#[inline]
+fn pext(occ: u64, mask: u64) -> usize {
+    // SAFETY: bmi2 is enabled for the whole build, checked above
+    unsafe { _pext_u64(occ, mask) as usize }
+}
+
+static ROOK_ENTRIES: [Entry; 64] = entries(ROOK_DIRS, None);
+static BISHOP_ENTRIES: [Entry; 64] = entries(BISHOP_DIRS, None);
+static ROOK_TABLE: [u64; ROOK_SIZE] = table(ROOK_DIRS, None);
+static BISHOP_TABLE: [u64; BISHOP_SIZE] = table(BISHOP_DIRS, None);
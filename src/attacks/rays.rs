@@ -0,0 +1,128 @@
+// Slow ray walks for building the lookup tables at compile time.
+
+pub const ROOK_DIRS: [(i8, i8); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+pub const BISHOP_DIRS: [(i8, i8); 4] = [(1, 1), (1, -1), (-1, 1), (-1, -1)];
+
+// sum of 2^(relevant squares) over all squares
+pub const ROOK_SIZE: usize = 102_400;
+pub const BISHOP_SIZE: usize = 5_248;
+
+// the squares along each ray that have another square behind them,
+// as a piece on the last square blocks nothing
+pub const fn blockers(sq: usize, dirs: [(i8, i8); 4]) -> u64 {
+    let mut mask = 0;
+    let mut dir = 0;
+    while dir < 4 {
+        let (df, dr) = dirs[dir];
+        let mut file = sq as i8 % 8 + df;
+        let mut rank = sq as i8 / 8 + dr;
+        while on_board(file + df, rank + dr) {
+            mask |= 1 << (8 * rank + file);
+            file += df;
+            rank += dr;
+        }
+        dir += 1;
+    }
+    mask
+}
+
+const fn on_board(file: i8, rank: i8) -> bool {
+    file >= 0 && file < 8 && rank >= 0 && rank < 8
+}
+
+// walks each ray until it leaves the board or hits a piece
+pub const fn attacks(sq: usize, occ: u64, dirs: [(i8, i8); 4]) -> u64 {
+    let mut attacks = 0;
+    let mut dir = 0;
+    while dir < 4 {
+        let (df, dr) = dirs[dir];
+        let mut file = sq as i8 % 8 + df;
+        let mut rank = sq as i8 / 8 + dr;
+        while on_board(file, rank) {
+            let bit = 1 << (8 * rank + file);
+            attacks |= bit;
+            if occ & bit > 0 {
+                break;
+            }
+            file += df;
+            rank += dr;
+        }
+        dir += 1;
+    }
+    attacks
+}
+
+// squares that can block a slider on each square, its magic and shift
+// when indexed by one, and where its attacks start in the table
+#[derive(Clone, Copy)]
+pub struct Entry {
+    pub mask: u64,
+    pub magic: u64,
+    pub shift: u32,
+    pub offset: usize,
+}
+
+impl Entry {
+    #[inline]
+    pub const fn magic_index(&self, occ: u64) -> usize {
+        self.offset + ((occ & self.mask).wrapping_mul(self.magic) >> self.shift) as usize
+    }
+}
+
+// with no magics the blockers are packed in the order pext packs them
+pub const fn entries(dirs: [(i8, i8); 4], magics: Option<&[u64; 64]>) -> [Entry; 64] {
+    let empty = Entry {
+        mask: 0,
+        magic: 0,
+        shift: 0,
+        offset: 0,
+    };
+    let mut entries = [empty; 64];
+    let mut offset = 0;
+    let mut sq = 0;
+    while sq < 64 {
+        let mask = blockers(sq, dirs);
+        let bits = mask.count_ones();
+        entries[sq] = Entry {
+            mask,
+            magic: match magics {
+                Some(magics) => magics[sq],
+                None => 0,
+            },
+            shift: 64 - bits,
+            offset,
+        };
+        offset += 1 << bits;
+        sq += 1;
+    }
+    entries
+}
+
+pub const fn table<const SIZE: usize>(
+    dirs: [(i8, i8); 4],
+    magics: Option<&[u64; 64]>,
+) -> [u64; SIZE] {
+    let entries = entries(dirs, magics);
+    let mut table = [0; SIZE];
+    let mut sq = 0;
+    while sq < 64 {
+        let entry = entries[sq];
+        // counting through the subsets of the mask visits them in pext order
+        let mut occ = 0;
+        let mut count = 0;
+        loop {
+            let idx = match magics {
+                Some(_) => entry.magic_index(occ),
+                None => entry.offset + count,
+            };
+            table[idx] = attacks(sq, occ, dirs);
+            occ = occ.wrapping_sub(entry.mask) & entry.mask;
+            count += 1;
+            if occ == 0 {
+                break;
+            }
+        }
+        sq += 1;
+    }
+    table
+}
@@ -0,0 +1,125 @@
+#![warn(clippy::pedantic)]
+#![allow(clippy::cast_possible_truncation)]
+#![allow(clippy::cast_precision_loss)]
+
+use perft::{perft, Attacks, Classic, Piece, Position, Sliders};
+use std::{fs, hint::black_box, process, time::Instant};
+
+const USAGE: &str = "\
+Usage: bench [PATH]
+
+Times each slider attack backend built into this binary on the positions
+in an EPD suite (default: perft_results.txt), then runs perft on them with
+the backend chosen by cargo features.
+
+Build with '--features pext,magic' to include every backend.";
+
+// largest expected count of a depth to run perft to
+const MAX_NODES: u64 = 5_000_000;
+
+// times the attacks of every slider are looked up
+const ROUNDS: usize = 10_000;
+
+fn main() {
+    let path = match std::env::args().nth(1) {
+        Some(arg) if arg == "-h" || arg == "--help" => {
+            println!("{USAGE}");
+            return;
+        }
+        Some(path) => path,
+        None => "perft_results.txt".into(),
+    };
+
+    let suite = load(&path).unwrap_or_else(|err| {
+        eprintln!("error: {err}");
+        process::exit(1);
+    });
+
+    let sliders = sliders(&suite);
+    println!("{} slider lookups per round", sliders.len());
+    lookups::<Classic>(&sliders);
+    #[cfg(feature = "magic")]
+    lookups::<perft::Magic>(&sliders);
+    #[cfg(feature = "pext")]
+    lookups::<perft::Pext>(&sliders);
+
+    let initial = Instant::now();
+    let mut total = 0;
+    for (pos, counts) in &suite {
+        for (d, _) in counts.iter().enumerate().filter(|(_, &n)| n <= MAX_NODES) {
+            total += perft::<false, true>(pos, d as u8 + 1);
+        }
+    }
+    let dur = initial.elapsed();
+    println!(
+        "perft ({}) time {} nodes {total} nps {:.3}",
+        Attacks::BACKEND,
+        dur.as_millis(),
+        total as f64 / dur.as_micros() as f64
+    );
+}
+
+fn load(path: &str) -> Result<Vec<(Position, Vec<u64>)>, String> {
+    let text = fs::read_to_string(path).map_err(|err| format!("could not read '{path}': {err}"))?;
+    text.lines()
+        .enumerate()
+        .map(|(idx, line)| {
+            let mut fields = line.split(';');
+            let pos = Position::from_fen(fields.next().unwrap_or(""))
+                .map_err(|err| format!("{path}:{}: invalid fen: {err}", idx + 1))?;
+            let counts = fields
+                .filter_map(|depth| depth.split_whitespace().nth(1)?.parse().ok())
+                .collect();
+            Ok((pos, counts))
+        })
+        .collect()
+}
+
+// (square, occupancy, is rook) for every slider in the suite and the
+// positions one move in, so the lookups see realistic boards
+fn sliders(suite: &[(Position, Vec<u64>)]) -> Vec<(usize, u64, bool)> {
+    let mut sliders = Vec::new();
+    for (pos, _) in suite {
+        let moves = pos.gen();
+        let children = moves.list[..moves.len].iter().map(|&mov| {
+            let mut tmp = *pos;
+            tmp.make(mov);
+            tmp
+        });
+
+        for child in std::iter::once(*pos).chain(children) {
+            let queens = child.piece(Piece::QUEEN);
+            for (piece, rook) in [(Piece::ROOK, true), (Piece::BISHOP, false)] {
+                let mut bb = child.piece(piece) | queens;
+                while bb > 0 {
+                    sliders.push((bb.trailing_zeros() as usize, child.occ(), rook));
+                    bb &= bb - 1;
+                }
+            }
+        }
+    }
+    sliders
+}
+
+fn lookups<S: Sliders>(sliders: &[(usize, u64, bool)]) {
+    let initial = Instant::now();
+    let mut acc = 0;
+    for _ in 0..ROUNDS {
+        for &(sq, occ, rook) in black_box(sliders) {
+            acc ^= if rook {
+                S::rook(sq, occ)
+            } else {
+                S::bishop(sq, occ)
+            };
+        }
+    }
+    black_box(acc);
+
+    let dur = initial.elapsed();
+    let lookups = (ROUNDS * sliders.len()) as f64;
+    println!(
+        "{:<8} {:>8.3} ns per lookup",
+        S::NAME,
+        dur.as_nanos() as f64 / lookups
+    );
+}
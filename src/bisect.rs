@@ -0,0 +1,283 @@
+use super::{position::Position, threads::perft_parallel};
+use std::{
+    io::{BufRead, BufReader, Write},
+    process::{Child, ChildStdin, ChildStdout, Command, Stdio},
+};
+
+/// Anything that can give per-move perft counts for a position.
+pub trait Reference {
+    /// Counts for each legal move after playing the UCI `moves` from `fen`.
+    /// # Errors
+    /// Returns a message if the counts could not be obtained.
+    fn divide(
+        &mut self,
+        fen: &str,
+        moves: &[String],
+        depth: u8,
+    ) -> Result<Vec<(String, u64)>, String>;
+}
+
+/// A reference engine run as a child process and queried with `go perft`.
+pub struct UciEngine {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl UciEngine {
+    /// Starts the engine and waits for `uciok`, the command may include
+    /// arguments, such as `perft --uci`.
+    /// # Errors
+    /// Returns a message if the engine cannot be started or exits early.
+    /// # Panics
+    /// Never panics, as the engine's stdin and stdout are always piped.
+    pub fn spawn(cmd: &str) -> Result<Self, String> {
+        let mut args = cmd.split_whitespace();
+        let path = args.next().ok_or("no reference engine given")?;
+
+        let mut child = Command::new(path)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|err| format!("could not start '{path}': {err}"))?;
+
+        let stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = BufReader::new(child.stdout.take().expect("stdout is piped"));
+        let mut engine = Self {
+            child,
+            stdin,
+            stdout,
+        };
+
+        engine.send("uci")?;
+        while engine.read_line()? != "uciok" {}
+
+        Ok(engine)
+    }
+
+    /// Sends `setoption`, such as `UCI_Chess960` for positions with Chess960 castling.
+    /// # Errors
+    /// Returns a message if the engine cannot be written to.
+    pub fn set_option(&mut self, name: &str, value: &str) -> Result<(), String> {
+        self.send(&format!("setoption name {name} value {value}"))
+    }
+
+    fn send(&mut self, cmd: &str) -> Result<(), String> {
+        writeln!(self.stdin, "{cmd}")
+            .and_then(|()| self.stdin.flush())
+            .map_err(|err| format!("could not write to reference engine: {err}"))
+    }
+
+    fn read_line(&mut self) -> Result<String, String> {
+        let mut line = String::new();
+        match self.stdout.read_line(&mut line) {
+            Ok(0) => Err("reference engine exited".into()),
+            Ok(_) => Ok(line.trim().to_string()),
+            Err(err) => Err(format!("could not read from reference engine: {err}")),
+        }
+    }
+}
+
+impl Reference for UciEngine {
+    fn divide(
+        &mut self,
+        fen: &str,
+        moves: &[String],
+        depth: u8,
+    ) -> Result<Vec<(String, u64)>, String> {
+        if moves.is_empty() {
+            self.send(&format!("position fen {fen}"))?;
+        } else {
+            self.send(&format!("position fen {fen} moves {}", moves.join(" ")))?;
+        }
+        self.send(&format!("go perft {depth}"))?;
+
+        let mut counts = Vec::new();
+        loop {
+            let line = self.read_line()?;
+            if line.starts_with("Nodes searched") {
+                return Ok(counts);
+            }
+
+            if let Some((mov, num)) = line.split_once(':') {
+                if let Ok(num) = num.trim().parse() {
+                    counts.push((mov.trim().to_string(), num));
+                }
+            }
+        }
+    }
+}
+
+impl Drop for UciEngine {
+    fn drop(&mut self) {
+        let _ = self.send("quit");
+        let _ = self.child.wait();
+    }
+}
+
+/// A move whose subtree counts differ.
+pub struct Step {
+    /// The move in UCI notation.
+    pub mov: String,
+    /// Our count.
+    pub ours: u64,
+    /// The reference's count.
+    pub theirs: u64,
+}
+
+/// Where the two move generators first disagree.
+pub struct Divergence {
+    /// Moves followed from the root, one per depth.
+    pub line: Vec<Step>,
+    /// Position reached by the line.
+    pub pos: Position,
+    /// Legal moves in `pos` that the reference did not give.
+    pub missing: Vec<String>,
+    /// Moves the reference gave that are not legal in `pos`.
+    pub extra: Vec<String>,
+}
+
+/// Follows the first move with a differing count down to the position
+/// where the move lists themselves differ, or gives `None` if the counts agree.
+/// # Errors
+/// Returns a message if the reference fails.
+pub fn bisect<R: Reference>(
+    reference: &mut R,
+    root: &Position,
+    depth: u8,
+    threads: usize,
+) -> Result<Option<Divergence>, String> {
+    let fen = root.to_fen();
+    let chess960 = root.is_chess960();
+    let mut pos = *root;
+    let mut line = Vec::new();
+    let mut moves = Vec::new();
+
+    for d in (1..=depth).rev() {
+        let ours = perft_parallel::<true>(&pos, d, threads);
+        let theirs = reference.divide(&fen, &moves, d)?;
+
+        let missing: Vec<String> = ours
+            .iter()
+            .map(|(mov, _)| mov.to_uci(chess960))
+            .filter(|uci| theirs.iter().all(|(mov, _)| mov != uci))
+            .collect();
+        let extra: Vec<String> = theirs
+            .iter()
+            .map(|(mov, _)| mov.clone())
+            .filter(|uci| ours.iter().all(|(mov, _)| mov.to_uci(chess960) != *uci))
+            .collect();
+
+        if !missing.is_empty() || !extra.is_empty() {
+            return Ok(Some(Divergence {
+                line,
+                pos,
+                missing,
+                extra,
+            }));
+        }
+
+        let differing = ours.iter().find_map(|&(mov, num)| {
+            let uci = mov.to_uci(chess960);
+            let theirs = theirs.iter().find(|(other, _)| *other == uci)?.1;
+            (theirs != num).then_some((mov, uci, num, theirs))
+        });
+
+        let Some((mov, uci, ours, theirs)) = differing else {
+            return Ok(None);
+        };
+
+        pos.make(mov);
+        moves.push(uci.clone());
+        line.push(Step {
+            mov: uci,
+            ours,
+            theirs,
+        });
+    }
+
+    Ok(Some(Divergence {
+        line,
+        pos,
+        missing: Vec::new(),
+        extra: Vec::new(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{bisect, Reference};
+    use crate::{consts::Flag, position::Position};
+
+    // stand-in engine that does not know about en passant
+    struct NoEnPassant;
+
+    fn count(pos: &Position, depth: u8) -> u64 {
+        let moves = pos.gen();
+        let mut total = 0;
+        for &mov in moves.list[..moves.len]
+            .iter()
+            .filter(|mov| mov.flag() != Flag::ENP)
+        {
+            let mut tmp = *pos;
+            tmp.make(mov);
+            total += if depth == 1 {
+                1
+            } else {
+                count(&tmp, depth - 1)
+            };
+        }
+        total
+    }
+
+    impl Reference for NoEnPassant {
+        fn divide(
+            &mut self,
+            fen: &str,
+            moves: &[String],
+            depth: u8,
+        ) -> Result<Vec<(String, u64)>, String> {
+            let mut pos = Position::parse_fen(fen);
+            pos.play_uci_moves(&moves.join(" ")).unwrap();
+
+            let list = pos.gen();
+            Ok(list.list[..list.len]
+                .iter()
+                .filter(|mov| mov.flag() != Flag::ENP)
+                .map(|&mov| {
+                    let mut tmp = pos;
+                    tmp.make(mov);
+                    let num = if depth == 1 {
+                        1
+                    } else {
+                        count(&tmp, depth - 1)
+                    };
+                    (mov.to_uci(pos.is_chess960()), num)
+                })
+                .collect())
+        }
+    }
+
+    #[test]
+    fn finds_missing_en_passant() {
+        let pos = Position::parse_fen("rnbqkbnr/pppppppp/8/4P3/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 2");
+        let div = bisect(&mut NoEnPassant, &pos, 3, 1).unwrap().unwrap();
+
+        assert_eq!(div.line.len(), 1);
+        assert!(div.line[0].ours > div.line[0].theirs);
+        assert!(div.extra.is_empty());
+        assert_eq!(div.missing.len(), 1);
+
+        let mut expected = pos;
+        expected.play_uci_moves(&div.line[0].mov).unwrap();
+        let enp = expected.parse_uci_move(&div.missing[0]).unwrap();
+        assert_eq!(enp.flag(), Flag::ENP);
+    }
+
+    #[test]
+    fn agrees_without_en_passant() {
+        let pos = Position::parse_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1");
+        assert!(bisect(&mut NoEnPassant, &pos, 3, 1).unwrap().is_none());
+    }
+}
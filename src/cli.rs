@@ -0,0 +1,298 @@
+use perft::{random::RandomSettings, STARTPOS};
+
+pub const USAGE: &str = "\
+Usage: perft [OPTIONS]
+
+With no options, every position in perft_results.txt is checked.
+
+Options:
+  --fen <FEN>       position to run (default: start position)
+  --moves <MOVES>   play these UCI moves from the position first
+  --depth <N>       run a single position to depth N
+  --bulk            count moves at depth 1 instead of making them
+  --no-bulk         make every move down to the leaves (default)
+  --unmake          make and unmake moves in place instead of copying the position
+  --divide          print the count for each root move
+  --progress        report progress on stderr after each root move
+  --stats           print captures, checks, mates etc. for each depth
+  --uci             read 'position' and 'go perft' commands from stdin
+  --bisect <ENGINE> find where a UCI engine's 'go perft' counts first differ
+  --suite <PATH>    check every position in an EPD suite
+  --generate <N>    print N random positions from '--fen' as EPD lines with
+                    counts to '--depth' (default: 3)
+  --seed <N>        seed for '--generate' (default: 1)
+  --plies <MIN-MAX> plies played for each generated position (default: 10-80)
+  --pieces <MIN-MAX>
+                    pieces left in each generated position (default: 2-32)
+  --bias <EP,PROMO,CASTLE>
+                    chances in percent that a generated game looks for an
+                    en passant, promotion or castling move (default: 10,10,10)
+  --chess960        write castling as the king taking its own rook
+  --threads <N>     split the search across N threads (default: 1)
+  --hash <MB>       cache subtree counts in a hash table of MB megabytes
+  -h, --help        print this message";
+
+pub enum Mode {
+    Help,
+    Uci,
+    Suite(String),
+    Single {
+        fen: String,
+        depth: u8,
+        divide: bool,
+    },
+    Stats {
+        fen: String,
+        depth: u8,
+    },
+    Bisect {
+        fen: String,
+        depth: u8,
+        engine: String,
+    },
+    Generate {
+        fen: String,
+        depth: u8,
+        count: usize,
+        seed: u64,
+        settings: RandomSettings,
+    },
+}
+
+#[allow(clippy::struct_excessive_bools)]
+pub struct Options {
+    pub mode: Mode,
+    pub bulk: bool,
+    pub threads: usize,
+    pub hash: Option<usize>,
+    pub moves: Option<String>,
+    pub chess960: bool,
+    pub unmake: bool,
+    pub progress: bool,
+}
+
+impl Options {
+    #[allow(clippy::too_many_lines)]
+    pub fn parse<I: Iterator<Item = String>>(mut args: I) -> Result<Self, String> {
+        let mut fen = None;
+        let mut moves = None;
+        let mut depth = None;
+        let mut suite = None;
+        let mut divide = false;
+        let mut stats = false;
+        let mut uci = false;
+        let mut bisect = None;
+        let mut bulk = false;
+        let mut threads = 1;
+        let mut hash = None;
+        let mut chess960 = false;
+        let mut unmake = false;
+        let mut progress = false;
+        let mut generate = None;
+        let mut seed = 1;
+        let mut settings = RandomSettings::default();
+
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| format!("missing value for '{arg}'"))
+            };
+
+            match arg.as_str() {
+                "-h" | "--help" => {
+                    return Ok(Self {
+                        mode: Mode::Help,
+                        bulk,
+                        threads,
+                        hash,
+                        moves,
+                        chess960,
+                        unmake,
+                        progress,
+                    })
+                }
+                "--fen" => fen = Some(value()?),
+                "--moves" => moves = Some(value()?),
+                "--depth" => depth = Some(parse_depth(&value()?)?),
+                "--suite" => suite = Some(value()?),
+                "--bulk" => bulk = true,
+                "--no-bulk" => bulk = false,
+                "--unmake" => unmake = true,
+                "--divide" => divide = true,
+                "--progress" => progress = true,
+                "--stats" => stats = true,
+                "--uci" => uci = true,
+                "--bisect" => bisect = Some(value()?),
+                "--threads" => threads = parse_threads(&value()?)?,
+                "--hash" => hash = Some(parse_hash(&value()?)?),
+                "--chess960" => chess960 = true,
+                "--generate" => generate = Some(parse_count(&value()?)?),
+                "--seed" => seed = parse_seed(&value()?)?,
+                "--plies" => {
+                    (settings.min_ply, settings.max_ply) = parse_range(&arg, &value()?)?;
+                }
+                "--pieces" => {
+                    (settings.min_pieces, settings.max_pieces) = parse_range(&arg, &value()?)?;
+                }
+                "--bias" => {
+                    [settings.en_passant, settings.promotion, settings.castling] =
+                        parse_bias(&value()?)?;
+                }
+                _ => return Err(format!("unknown argument '{arg}'")),
+            }
+        }
+
+        if bisect.is_some() && (stats || divide) {
+            return Err("'--bisect' cannot be used with '--stats' or '--divide'".into());
+        }
+
+        if stats && divide {
+            return Err("'--stats' cannot be used with '--divide'".into());
+        }
+
+        if hash.is_some() && threads > 1 {
+            return Err("'--hash' cannot be used with '--threads'".into());
+        }
+
+        if unmake && (hash.is_some() || threads > 1) {
+            return Err("'--unmake' cannot be used with '--hash' or '--threads'".into());
+        }
+
+        if progress && threads > 1 {
+            return Err("'--progress' cannot be used with '--threads'".into());
+        }
+
+        if uci && (fen.is_some() || moves.is_some() || depth.is_some() || suite.is_some()) {
+            return Err("'--uci' takes positions from stdin, not the command line".into());
+        }
+
+        if generate.is_some() && (suite.is_some() || uci || bisect.is_some() || stats || divide) {
+            return Err(
+                "'--generate' cannot be used with '--suite', '--uci', '--bisect', '--stats' or '--divide'"
+                    .into(),
+            );
+        }
+
+        if let Some(count) = generate {
+            return Ok(Self {
+                mode: Mode::Generate {
+                    fen: fen.unwrap_or_else(|| STARTPOS.into()),
+                    depth: depth.unwrap_or(3),
+                    count,
+                    seed,
+                    settings,
+                },
+                bulk,
+                threads,
+                hash,
+                moves,
+                chess960,
+                unmake,
+                progress,
+            });
+        }
+
+        let mode = match (suite, depth) {
+            (Some(_), Some(_)) => return Err("'--suite' cannot be used with '--depth'".into()),
+            (Some(_), None) if fen.is_some() => {
+                return Err("'--suite' cannot be used with '--fen'".into())
+            }
+            (None, None) if fen.is_some() => return Err("'--fen' requires '--depth'".into()),
+            (_, None) if moves.is_some() => return Err("'--moves' requires '--depth'".into()),
+            (_, None) if divide => return Err("'--divide' requires '--depth'".into()),
+            (_, None) if stats => return Err("'--stats' requires '--depth'".into()),
+            (_, None) if bisect.is_some() => return Err("'--bisect' requires '--depth'".into()),
+            (None, None) if uci => Mode::Uci,
+            (suite, None) => Mode::Suite(suite.unwrap_or_else(|| "perft_results.txt".into())),
+            (None, Some(depth)) if bisect.is_some() => Mode::Bisect {
+                fen: fen.unwrap_or_else(|| STARTPOS.into()),
+                depth,
+                engine: bisect.unwrap_or_default(),
+            },
+            (None, Some(depth)) if stats => Mode::Stats {
+                fen: fen.unwrap_or_else(|| STARTPOS.into()),
+                depth,
+            },
+            (None, Some(depth)) => Mode::Single {
+                fen: fen.unwrap_or_else(|| STARTPOS.into()),
+                depth,
+                divide,
+            },
+        };
+
+        Ok(Self {
+            mode,
+            bulk,
+            threads,
+            hash,
+            moves,
+            chess960,
+            unmake,
+            progress,
+        })
+    }
+}
+
+fn parse_depth(arg: &str) -> Result<u8, String> {
+    match arg.parse() {
+        Ok(0) | Err(_) => Err(format!(
+            "invalid depth '{arg}', expected a number from 1 to 255"
+        )),
+        Ok(depth) => Ok(depth),
+    }
+}
+
+fn parse_threads(arg: &str) -> Result<usize, String> {
+    match arg.parse() {
+        Ok(0) | Err(_) => Err(format!(
+            "invalid thread count '{arg}', expected a positive number"
+        )),
+        Ok(threads) => Ok(threads),
+    }
+}
+
+fn parse_hash(arg: &str) -> Result<usize, String> {
+    match arg.parse() {
+        Ok(0) | Err(_) => Err(format!(
+            "invalid hash size '{arg}', expected a positive number of megabytes"
+        )),
+        Ok(megabytes) => Ok(megabytes),
+    }
+}
+
+fn parse_count(arg: &str) -> Result<usize, String> {
+    match arg.parse() {
+        Ok(0) | Err(_) => Err(format!(
+            "invalid position count '{arg}', expected a positive number"
+        )),
+        Ok(count) => Ok(count),
+    }
+}
+
+fn parse_seed(arg: &str) -> Result<u64, String> {
+    arg.parse()
+        .map_err(|_| format!("invalid seed '{arg}', expected a number"))
+}
+
+fn parse_range<T: std::str::FromStr + PartialOrd>(name: &str, arg: &str) -> Result<(T, T), String> {
+    let err = || format!("invalid range '{arg}' for '{name}', expected <MIN>-<MAX>");
+    let (min, max) = arg.split_once('-').ok_or_else(err)?;
+    let (min, max) = (
+        min.parse().map_err(|_| err())?,
+        max.parse().map_err(|_| err())?,
+    );
+    if min > max {
+        return Err(err());
+    }
+    Ok((min, max))
+}
+
+fn parse_bias(arg: &str) -> Result<[u8; 3], String> {
+    let err = || format!("invalid bias '{arg}', expected three percentages such as '10,10,10'");
+    let percents = arg
+        .split(',')
+        .map(|percent| percent.parse().ok().filter(|&p| p <= 100))
+        .collect::<Option<Vec<u8>>>()
+        .ok_or_else(err)?;
+    percents.try_into().map_err(|_| err())
+}
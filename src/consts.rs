@@ -1,78 +1,84 @@
-use super::init;
-
+/// Indices of the colour bitboards in [`Position::piece`](crate::Position::piece).
 pub struct Side;
 impl Side {
+    /// White.
     pub const WHITE: usize = 0;
+    /// Black.
     pub const BLACK: usize = 1;
 }
 
+/// Indices of the piece bitboards in [`Position::piece`](crate::Position::piece).
 pub struct Piece;
 impl Piece {
+    /// No piece.
     pub const EMPTY: usize = 0;
+    /// Pawn.
     pub const PAWN: usize = 2;
+    /// Knight.
     pub const KNIGHT: usize = 3;
+    /// Bishop.
     pub const BISHOP: usize = 4;
+    /// Rook.
     pub const ROOK: usize = 5;
+    /// Queen.
     pub const QUEEN: usize = 6;
+    /// King.
     pub const KING: usize = 7;
 }
 
+/// Move flags, as returned by [`Move::flag`](crate::Move::flag).
+///
+/// All captures have [`Flag::CAP`] set and all promotions have [`Flag::NPR`] set.
 pub struct Flag;
 impl Flag {
+    /// Quiet move.
     pub const QUIET: u8 = 0;
+    /// Pawn double push.
     pub const DBL: u8 = 1;
+    /// Kingside castle.
     pub const KS: u8 = 2;
+    /// Queenside castle.
     pub const QS: u8 = 3;
+    /// Capture.
     pub const CAP: u8 = 4;
+    /// En passant capture.
     pub const ENP: u8 = 5;
+    /// Knight promotion.
     pub const NPR: u8 = 8;
+    /// Bishop promotion.
     pub const BPR: u8 = 9;
+    /// Rook promotion.
     pub const RPR: u8 = 10;
+    /// Queen promotion.
     pub const QPR: u8 = 11;
+    /// Knight promotion with capture.
     pub const NPC: u8 = 12;
+    /// Bishop promotion with capture.
     pub const BPC: u8 = 13;
+    /// Rook promotion with capture.
     pub const RPC: u8 = 14;
+    /// Queen promotion with capture.
     pub const QPC: u8 = 15;
 }
 
-// castle rights
+/// Castling right bits, as returned by [`Position::rights`](crate::Position::rights).
 pub struct Right;
 impl Right {
+    /// White queenside.
     pub const WQS: u8 = 0b1000;
+    /// White kingside.
     pub const WKS: u8 = 0b0100;
+    /// Black queenside.
     pub const BQS: u8 = 0b0010;
+    /// Black kingside.
     pub const BKS: u8 = 0b0001;
+    /// Rights indexed by side, then kingside.
     pub const TABLE: [[u8; 2]; 2] = [[Self::WQS, Self::WKS], [Self::BQS, Self::BKS]];
 }
 
-// paths required to be clear for castling
-pub struct Path;
-impl Path {
-    pub const BD1: u64 = 0x0000_0000_0000_000E;
-    pub const FG1: u64 = 0x0000_0000_0000_0060;
-    pub const BD8: u64 = 0x0E00_0000_0000_0000;
-    pub const FG8: u64 = 0x6000_0000_0000_0000;
-    pub const TABLE: [[u64; 2]; 2] = [[Self::BD1, Self::FG1], [Self::BD8, Self::FG8]];
-}
-
-// the castling rook move bitboards
-pub const ROOK_MOVES: [[u64; 2]; 2] = [
-    [0x0000_0000_0000_0009, 0x0900_0000_0000_0000],
-    [0x0000_0000_0000_00A0, 0xA000_0000_0000_0000],
-];
-
-// mask off castling rights by square
-pub const CASTLE_MASK: [u8; 64] = init! {idx,
-    match idx {
-         0 =>  7,
-         4 =>  3,
-         7 => 11,
-        56 => 13,
-        60 => 12,
-        63 => 14,
-         _ => 15
-    }
-};
+// files the king and rook land on when castling, indexed by kingside
+pub const CASTLE_KING_FILE: [u8; 2] = [2, 6];
+pub const CASTLE_ROOK_FILE: [u8; 2] = [3, 5];
 
 // for promotions / double pushes
 pub struct Rank;
@@ -81,7 +87,7 @@ impl Rank {
     pub const DBL: [u64; 2] = [0x0000_0000_FF00_0000, 0x0000_00FF_0000_0000];
 }
 
-pub const IN_BETWEEN: [[u64; 64]; 64] = {
+pub static IN_BETWEEN: [[u64; 64]; 64] = {
     let mut arr = [[0; 64]; 64];
     let mut i = 0;
     while i < 64 {
@@ -95,7 +101,7 @@ pub const IN_BETWEEN: [[u64; 64]; 64] = {
     arr
 };
 
-pub const LINE_THROUGH: [[u64; 64]; 64] = {
+pub static LINE_THROUGH: [[u64; 64]; 64] = {
     let mut arr = [[0; 64]; 64];
     let mut i = 0;
     while i < 64 {
@@ -124,3 +130,60 @@ const fn in_between(sq1: usize, sq2: usize) -> u64 {
     line = line.wrapping_mul(btwn & btwn.wrapping_neg());
     line & btwn
 }
+
+// zobrist hashing keys
+pub struct Zobrist {
+    pub pieces: [[[u64; 64]; 8]; 2],
+    pub castle: [u64; 16],
+    pub enp: [u64; 8],
+    pub side: u64,
+}
+
+pub static ZVALS: Zobrist = {
+    let mut vals = Zobrist {
+        pieces: [[[0; 64]; 8]; 2],
+        castle: [0; 16],
+        enp: [0; 8],
+        side: 0,
+    };
+    let mut seed = 180_620_142;
+
+    let mut side = 0;
+    while side < 2 {
+        let mut pc = Piece::PAWN;
+        while pc <= Piece::KING {
+            let mut sq = 0;
+            while sq < 64 {
+                seed = xorshift(seed);
+                vals.pieces[side][pc][sq] = seed;
+                sq += 1;
+            }
+            pc += 1;
+        }
+        side += 1;
+    }
+
+    let mut i = 0;
+    while i < 16 {
+        seed = xorshift(seed);
+        vals.castle[i] = seed;
+        i += 1;
+    }
+
+    i = 0;
+    while i < 8 {
+        seed = xorshift(seed);
+        vals.enp[i] = seed;
+        i += 1;
+    }
+
+    vals.side = xorshift(seed);
+    vals
+};
+
+pub const fn xorshift(mut seed: u64) -> u64 {
+    seed ^= seed << 13;
+    seed ^= seed >> 7;
+    seed ^= seed << 17;
+    seed
+}
@@ -0,0 +1,100 @@
+use super::position::{IllegalMove, Move, Position, Undo};
+
+/// A position together with the moves that led to it, for detecting repetitions.
+///
+/// Only positions since the last irreversible move, a capture, a pawn move or
+/// a loss of castling rights, can repeat, so older ones are never looked at.
+pub struct Game {
+    pos: Position,
+    // hash of every position reached, the current one last
+    keys: Vec<u64>,
+    // for each entry in keys, index of the first position since the last irreversible move
+    starts: Vec<usize>,
+    // moves played, with what is needed to take them back
+    moves: Vec<(Move, Undo)>,
+}
+
+impl Game {
+    /// Starts a game from `pos`, which has no history.
+    #[must_use]
+    pub fn new(pos: Position) -> Self {
+        Self {
+            pos,
+            keys: vec![pos.hash()],
+            starts: vec![0],
+            moves: Vec::new(),
+        }
+    }
+
+    /// The current position.
+    #[must_use]
+    pub fn position(&self) -> &Position {
+        &self.pos
+    }
+
+    /// Number of moves played since the start of the game.
+    #[must_use]
+    pub fn ply(&self) -> usize {
+        self.moves.len()
+    }
+
+    /// Plays a legal move generated for the current position.
+    pub fn make(&mut self, mov: Move) {
+        let rights = self.pos.rights();
+        let undo = self.pos.make_undoable(mov);
+
+        let irreversible = self.pos.halfmove_clock() == 0 || self.pos.rights() != rights;
+        let start = if irreversible {
+            self.keys.len()
+        } else {
+            self.starts[self.starts.len() - 1]
+        };
+
+        self.keys.push(self.pos.hash());
+        self.starts.push(start);
+        self.moves.push((mov, undo));
+    }
+
+    /// Takes back the last move, returning it, or `None` at the start of the game.
+    pub fn unmake(&mut self) -> Option<Move> {
+        let (mov, undo) = self.moves.pop()?;
+        self.pos.unmake(mov, undo);
+        self.keys.pop();
+        self.starts.pop();
+        Some(mov)
+    }
+
+    /// Plays a line of UCI moves, as in [`Position::play_uci_moves`].
+    /// # Errors
+    /// Returns the first move that is not legal, the moves before it stay played.
+    pub fn play_uci_moves(&mut self, line: &str) -> Result<(), IllegalMove> {
+        for uci in line.split_whitespace().skip_while(|&word| word == "moves") {
+            let mov = self
+                .pos
+                .parse_uci_move(uci)
+                .ok_or_else(|| IllegalMove(uci.to_string()))?;
+            self.make(mov);
+        }
+
+        Ok(())
+    }
+
+    /// Whether the current position has occurred at least `n` times, counting itself,
+    /// so `is_repetition(3)` checks for threefold repetition.
+    #[must_use]
+    pub fn is_repetition(&self, n: usize) -> bool {
+        let current = self.keys.len() - 1;
+        let start = self.starts[current];
+        let key = self.keys[current];
+
+        // the same side must be to move, so only every other position can match
+        let seen = (start..current)
+            .rev()
+            .skip(1)
+            .step_by(2)
+            .filter(|&idx| self.keys[idx] == key)
+            .count();
+
+        seen + 1 >= n
+    }
+}
@@ -0,0 +1,191 @@
+//! A fast legal move generator for chess, with perft drivers built on top of it.
+//!
+//! ```
+//! use perft::{perft, Position, STARTPOS};
+//!
+//! let pos = Position::parse_fen(STARTPOS);
+//! assert_eq!(perft::<false, true>(&pos, 3), 8902);
+//! ```
+
+#![warn(clippy::pedantic)]
+#![warn(missing_docs)]
+#![allow(clippy::cast_possible_truncation)]
+#![allow(clippy::cast_precision_loss)]
+
+mod attacks;
+/// Finding where another move generator's perft counts first differ.
+pub mod bisect;
+mod consts;
+/// Game history and repetition detection.
+pub mod game;
+/// Legal move generation.
+pub mod movegen;
+/// Reading games from PGN files.
+pub mod pgn;
+/// Board representation, making moves and FEN parsing.
+pub mod position;
+/// Progress reporting for long perft runs.
+pub mod progress;
+/// Seeded random legal positions for growing test suites.
+pub mod random;
+/// Slow mailbox move generator for testing the fast one.
+pub mod reference;
+/// Standard Algebraic Notation.
+pub mod san;
+/// Perft with captures, checks, mates etc. counted at the leaves.
+pub mod stats;
+/// Detecting checkmate, stalemate and draws.
+pub mod status;
+/// Hash table for caching perft subtree counts.
+pub mod table;
+/// Perft split across multiple threads.
+pub mod threads;
+
+#[cfg(feature = "magic")]
+pub use attacks::Magic;
+#[cfg(feature = "pext")]
+pub use attacks::Pext;
+pub use attacks::{Attacks, Classic, Sliders};
+pub use consts::{Flag, Piece, Right, Side};
+pub use game::Game;
+pub use movegen::MoveList;
+pub use pgn::{PgnError, PgnReader};
+pub use position::{FenError, IllegalMove, Move, Position, Undo};
+pub use progress::{perft_with_progress, Progress};
+pub use stats::{perft_stats, PerftStats};
+pub use status::Status;
+pub use table::{HashTable, TableTooLarge};
+pub use threads::perft_parallel;
+
+/// The standard starting position.
+pub const STARTPOS: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+/// Counts the leaf nodes of the legal move tree of `pos` to `depth`.
+///
+/// With `ROOT` the count for each root move is printed as `move: count`,
+/// and with `BULK` the moves at depth 1 are counted rather than made.
+/// Depth 0 counts `pos` itself.
+#[must_use]
+pub fn perft<const ROOT: bool, const BULK: bool>(pos: &Position, depth: u8) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+
+    let moves = pos.gen();
+
+    if BULK && !ROOT && depth == 1 {
+        return moves.len as u64;
+    }
+
+    let mut positions = 0;
+    let leaf = depth == 1;
+
+    for m_idx in 0..moves.len {
+        let mut tmp = *pos;
+        tmp.make(moves.list[m_idx]);
+
+        let num = if leaf {
+            1
+        } else {
+            perft::<false, BULK>(&tmp, depth - 1)
+        };
+        positions += num;
+
+        if ROOT {
+            println!("{}: {num}", moves.list[m_idx].to_uci(pos.is_chess960()));
+        }
+    }
+
+    positions
+}
+
+/// Same as [`perft`], but each move is played on `pos` itself with
+/// [`Position::make_undoable`] and taken back with [`Position::unmake`].
+#[must_use]
+pub fn perft_unmake<const ROOT: bool, const BULK: bool>(pos: &mut Position, depth: u8) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+
+    let moves = pos.gen();
+
+    if BULK && !ROOT && depth == 1 {
+        return moves.len as u64;
+    }
+
+    let mut positions = 0;
+    let leaf = depth == 1;
+
+    for m_idx in 0..moves.len {
+        let mov = moves.list[m_idx];
+        let undo = pos.make_undoable(mov);
+
+        let num = if leaf {
+            1
+        } else {
+            perft_unmake::<false, BULK>(pos, depth - 1)
+        };
+        positions += num;
+
+        pos.unmake(mov, undo);
+
+        if ROOT {
+            println!("{}: {num}", mov.to_uci(pos.is_chess960()));
+        }
+    }
+
+    positions
+}
+
+/// Same as [`perft`], but subtree counts are cached in `table` by
+/// position hash and depth, so transpositions are only counted once.
+#[must_use]
+pub fn perft_hashed<const ROOT: bool, const BULK: bool>(
+    pos: &Position,
+    depth: u8,
+    table: &mut HashTable,
+) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+
+    // probing is more expensive than counting the last ply
+    let cached = !ROOT && depth > 1;
+
+    if cached {
+        if let Some(count) = table.get(pos.hash(), depth) {
+            return count;
+        }
+    }
+
+    let moves = pos.gen();
+
+    if BULK && !ROOT && depth == 1 {
+        return moves.len as u64;
+    }
+
+    let mut positions = 0;
+    let leaf = depth == 1;
+
+    for m_idx in 0..moves.len {
+        let mut tmp = *pos;
+        tmp.make(moves.list[m_idx]);
+
+        let num = if leaf {
+            1
+        } else {
+            perft_hashed::<false, BULK>(&tmp, depth - 1, table)
+        };
+        positions += num;
+
+        if ROOT {
+            println!("{}: {num}", moves.list[m_idx].to_uci(pos.is_chess960()));
+        }
+    }
+
+    if cached {
+        table.insert(pos.hash(), depth, positions);
+    }
+
+    positions
+}
@@ -2,16 +2,20 @@
 #![allow(clippy::cast_possible_truncation)]
 #![allow(clippy::cast_precision_loss)]
 
-mod attacks;
-mod consts;
-pub mod movegen;
-pub mod position;
+mod cli;
+mod uci;
 
-use position::Position;
+use cli::{Mode, Options, USAGE};
+use perft::{
+    bisect::{bisect, UciEngine},
+    perft, perft_hashed, perft_parallel, perft_stats, perft_unmake, perft_with_progress,
+    random::{to_epd, RandomPositions, RandomSettings, MAX_ABANDONED},
+    reference, FenError, HashTable, PerftStats, Position, Progress,
+};
 use std::{
     fs::File,
-    io::{BufRead, BufReader},
-    time::Instant,
+    io::{self, BufRead, BufReader},
+    time::{Duration, Instant},
 };
 
 struct PerftResult {
@@ -21,10 +25,10 @@ struct PerftResult {
 }
 
 impl PerftResult {
-    fn from_epd(epd: &str) -> Self {
+    fn from_epd(epd: &str) -> Result<Self, FenError> {
         let split = epd.split(';').collect::<Vec<&str>>();
         let mut result = Self {
-            pos: Position::parse_fen(split[0]),
+            pos: Position::from_fen(split[0])?,
             fen: String::from(split[0]),
             results: Vec::new(),
         };
@@ -40,30 +44,350 @@ impl PerftResult {
             );
         }
 
-        result
+        Ok(result)
     }
 }
 
 fn main() {
+    let opts = match Options::parse(std::env::args().skip(1)) {
+        Ok(opts) => opts,
+        Err(err) => {
+            eprintln!("error: {err}\n\n{USAGE}");
+            std::process::exit(2);
+        }
+    };
+
+    let mut table = match opts.hash.map(HashTable::new).transpose() {
+        Ok(table) => table,
+        Err(err) => {
+            eprintln!("error: {err}\n\n{USAGE}");
+            std::process::exit(2);
+        }
+    };
+
+    match &opts.mode {
+        Mode::Help => println!("{USAGE}"),
+        Mode::Uci => {
+            if let Err(err) = uci::uci_loop(&opts, table.as_mut()) {
+                eprintln!("error: {err}");
+                std::process::exit(1);
+            }
+        }
+        Mode::Suite(path) => match run_suite(path, &opts, table.as_mut()) {
+            Ok(mismatches) if mismatches.is_empty() => {}
+            Ok(mismatches) => {
+                print_mismatches(&mismatches);
+                std::process::exit(1);
+            }
+            Err(err) => {
+                eprintln!("error: {err}");
+                std::process::exit(1);
+            }
+        },
+        Mode::Single { fen, depth, divide } => {
+            let pos = position_or_exit(fen, &opts);
+
+            let initial = Instant::now();
+            let total = run(&pos, *depth, *divide, &opts, table.as_mut());
+
+            if *divide {
+                println!();
+            }
+
+            report(total, initial.elapsed());
+        }
+        Mode::Bisect { fen, depth, engine } => {
+            let pos = position_or_exit(fen, &opts);
+            if let Err(err) = run_bisect(&pos, *depth, engine, &opts) {
+                eprintln!("error: {err}");
+                std::process::exit(1);
+            }
+        }
+        Mode::Stats { fen, depth } => {
+            let pos = position_or_exit(fen, &opts);
+
+            println!(
+                "{:>5} {:>14} {:>12} {:>10} {:>10} {:>10} {:>10} {:>10} {:>10} {:>10}",
+                "depth",
+                "nodes",
+                "captures",
+                "e.p.",
+                "castles",
+                "promotions",
+                "checks",
+                "discovered",
+                "double",
+                "checkmates"
+            );
+
+            for d in 1..=*depth {
+                print_stats(d, &perft_stats(&pos, d));
+            }
+        }
+        Mode::Generate {
+            fen,
+            depth,
+            count,
+            seed,
+            settings,
+        } => {
+            let start = position_or_exit(fen, &opts);
+            if let Err(err) = run_generate(start, *depth, *count, *seed, *settings) {
+                eprintln!("error: {err}");
+                std::process::exit(1);
+            }
+        }
+    }
+}
+
+fn position_or_exit(fen: &str, opts: &Options) -> Position {
+    let mut pos = Position::from_fen(fen).unwrap_or_else(|err| {
+        eprintln!("error: invalid fen '{fen}': {err}");
+        std::process::exit(2);
+    });
+
+    if opts.chess960 {
+        pos.set_chess960(true);
+    }
+
+    if let Some(moves) = &opts.moves {
+        if let Err(err) = pos.play_uci_moves(moves) {
+            eprintln!("error: {err}");
+            std::process::exit(2);
+        }
+    }
+
+    pos
+}
+
+// the counts to depth 2 are checked against the slow reference generator,
+// deeper ones would take too long
+fn run_generate(
+    start: Position,
+    depth: u8,
+    count: usize,
+    seed: u64,
+    settings: RandomSettings,
+) -> Result<(), String> {
+    if start.gen().len == 0 {
+        return Err("the start position has no legal moves".into());
+    }
+
+    let mut positions = RandomPositions::new(start, seed, settings);
+    for generated in 0..count {
+        let Some(pos) = positions.next() else {
+            return Err(format!(
+                "gave up after {generated} of {count} positions, {MAX_ABANDONED} games in a row \
+                 could not reach a position matching '--plies', '--pieces' and '--bias'"
+            ));
+        };
+
+        for d in 1..=depth.min(2) {
+            let (fast, slow) = (perft::<false, true>(&pos, d), reference::perft(&pos, d));
+            if fast != slow {
+                return Err(format!(
+                    "{pos}: depth {d} counted {fast}, the reference generator counted {slow}"
+                ));
+            }
+        }
+
+        println!("{}", to_epd(&pos, depth));
+    }
+
+    Ok(())
+}
+
+fn run_bisect(pos: &Position, depth: u8, engine: &str, opts: &Options) -> Result<(), String> {
+    let mut engine = UciEngine::spawn(engine)?;
+    if pos.is_chess960() {
+        engine.set_option("UCI_Chess960", "true")?;
+    }
+
+    let Some(div) = bisect(&mut engine, pos, depth, opts.threads)? else {
+        println!("counts agree to depth {depth}");
+        return Ok(());
+    };
+
+    for (ply, step) in div.line.iter().enumerate() {
+        println!(
+            "depth {}: {} ours {} reference {}",
+            depth as usize - ply,
+            step.mov,
+            step.ours,
+            step.theirs
+        );
+    }
+
+    let line: Vec<&str> = div.line.iter().map(|step| step.mov.as_str()).collect();
+    println!("moves: {}", line.join(" "));
+    println!("position: {}", div.pos);
+    if !div.missing.is_empty() {
+        println!("missing from reference: {}", div.missing.join(" "));
+    }
+    if !div.extra.is_empty() {
+        println!("extra in reference: {}", div.extra.join(" "));
+    }
+
+    std::process::exit(1);
+}
+
+fn run(
+    pos: &Position,
+    depth: u8,
+    divide: bool,
+    opts: &Options,
+    table: Option<&mut HashTable>,
+) -> u64 {
+    if opts.progress {
+        return run_with_progress(pos, depth, divide, opts, table);
+    }
+
+    if let Some(table) = table {
+        return match (divide, opts.bulk) {
+            (true, true) => perft_hashed::<true, true>(pos, depth, table),
+            (true, false) => perft_hashed::<true, false>(pos, depth, table),
+            (false, true) => perft_hashed::<false, true>(pos, depth, table),
+            (false, false) => perft_hashed::<false, false>(pos, depth, table),
+        };
+    }
+
+    if opts.threads > 1 {
+        let counts = if opts.bulk {
+            perft_parallel::<true>(pos, depth, opts.threads)
+        } else {
+            perft_parallel::<false>(pos, depth, opts.threads)
+        };
+
+        if divide {
+            for (mov, num) in &counts {
+                println!("{}: {num}", mov.to_uci(pos.is_chess960()));
+            }
+        }
+
+        return counts.iter().map(|(_, num)| num).sum();
+    }
+
+    if opts.unmake {
+        let mut pos = *pos;
+        return match (divide, opts.bulk) {
+            (true, true) => perft_unmake::<true, true>(&mut pos, depth),
+            (true, false) => perft_unmake::<true, false>(&mut pos, depth),
+            (false, true) => perft_unmake::<false, true>(&mut pos, depth),
+            (false, false) => perft_unmake::<false, false>(&mut pos, depth),
+        };
+    }
+
+    match (divide, opts.bulk) {
+        (true, true) => perft::<true, true>(pos, depth),
+        (true, false) => perft::<true, false>(pos, depth),
+        (false, true) => perft::<false, true>(pos, depth),
+        (false, false) => perft::<false, false>(pos, depth),
+    }
+}
+
+fn run_with_progress(
+    pos: &Position,
+    depth: u8,
+    divide: bool,
+    opts: &Options,
+    mut table: Option<&mut HashTable>,
+) -> u64 {
+    let count = |pos: &Position, depth: u8| match table.as_deref_mut() {
+        Some(table) if opts.bulk => perft_hashed::<false, true>(pos, depth, table),
+        Some(table) => perft_hashed::<false, false>(pos, depth, table),
+        None if opts.unmake && opts.bulk => perft_unmake::<false, true>(&mut { *pos }, depth),
+        None if opts.unmake => perft_unmake::<false, false>(&mut { *pos }, depth),
+        None if opts.bulk => perft::<false, true>(pos, depth),
+        None => perft::<false, false>(pos, depth),
+    };
+
+    let report = |progress: &Progress| {
+        eprintln!(
+            "{}/{} moves nodes {} nps {:.3} eta {:.1}s",
+            progress.done,
+            progress.total,
+            progress.nodes,
+            progress.nps() / 1_000_000.0,
+            progress.eta().as_secs_f64()
+        );
+    };
+
+    if divide {
+        perft_with_progress::<true, _, _>(pos, depth, count, report)
+    } else {
+        perft_with_progress::<false, _, _>(pos, depth, count, report)
+    }
+}
+
+struct Mismatch {
+    fen: String,
+    depth: u8,
+    expected: u64,
+    got: u64,
+}
+
+fn run_suite(
+    path: &str,
+    opts: &Options,
+    mut table: Option<&mut HashTable>,
+) -> Result<Vec<Mismatch>, String> {
+    let read_err = |err: io::Error| format!("could not read '{path}': {err}");
+
     let mut positions = Vec::new();
-    let file = File::open("perft_results.txt").unwrap();
-    for line in BufReader::new(file).lines().map(Result::unwrap) {
-        positions.push(PerftResult::from_epd(&line));
+    let file = File::open(path).map_err(read_err)?;
+    for (idx, line) in BufReader::new(file).lines().enumerate() {
+        let result = PerftResult::from_epd(&line.map_err(read_err)?)
+            .map_err(|err| format!("{path}:{}: invalid fen: {err}", idx + 1))?;
+        positions.push(result);
     }
 
     let initial = Instant::now();
     let mut total: u64 = 0;
+    let mut mismatches = Vec::new();
 
     for PerftResult { pos, fen, results } in positions {
         println!("{fen}");
         for (d, &res) in results.iter().enumerate() {
-            let count = perft::<false, false>(&pos, d as u8 + 1);
+            let depth = d as u8 + 1;
+            let count = run(&pos, depth, false, opts, table.as_deref_mut());
             total += count;
-            assert_eq!(count, res);
+
+            if count != res {
+                println!("depth {depth} failed: expected {res}, got {count}");
+                mismatches.push(Mismatch {
+                    fen: fen.clone(),
+                    depth,
+                    expected: res,
+                    got: count,
+                });
+            }
         }
     }
 
-    let dur = initial.elapsed();
+    report(total, initial.elapsed());
+    Ok(mismatches)
+}
+
+fn print_mismatches(mismatches: &[Mismatch]) {
+    println!("\n{} mismatches:", mismatches.len());
+    println!(
+        "{:>5} {:>14} {:>14} {:>14}  fen",
+        "depth", "expected", "got", "difference"
+    );
+
+    for Mismatch {
+        fen,
+        depth,
+        expected,
+        got,
+    } in mismatches
+    {
+        let diff = i128::from(*got) - i128::from(*expected);
+        println!("{depth:>5} {expected:>14} {got:>14} {diff:>+14}  {fen}");
+    }
+}
+
+fn report(total: u64, dur: Duration) {
     println!(
         "total time {} nodes {total} nps {:.3}",
         dur.as_millis(),
@@ -71,32 +395,17 @@ fn main() {
     );
 }
 
-#[must_use]
-pub fn perft<const ROOT: bool, const BULK: bool>(pos: &Position, depth: u8) -> u64 {
-    let moves = pos.gen();
-
-    if BULK && !ROOT && depth == 1 {
-        return moves.len as u64;
-    }
-
-    let mut positions = 0;
-    let leaf = depth == 1;
-
-    for m_idx in 0..moves.len {
-        let mut tmp = *pos;
-        tmp.make(moves.list[m_idx]);
-
-        let num = if !BULK && leaf {
-            1
-        } else {
-            perft::<false, BULK>(&tmp, depth - 1)
-        };
-        positions += num;
-
-        if ROOT {
-            println!("{}: {num}", moves.list[m_idx].to_uci());
-        }
-    }
-
-    positions
+fn print_stats(depth: u8, stats: &PerftStats) {
+    println!(
+        "{depth:>5} {:>14} {:>12} {:>10} {:>10} {:>10} {:>10} {:>10} {:>10} {:>10}",
+        stats.nodes,
+        stats.captures,
+        stats.en_passants,
+        stats.castles,
+        stats.promotions,
+        stats.checks,
+        stats.discovered_checks,
+        stats.double_checks,
+        stats.checkmates
+    );
 }
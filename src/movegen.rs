@@ -1,6 +1,9 @@
 use super::{
     attacks::Attacks,
-    consts::{Flag, Path, Piece, Rank, Right, Side, IN_BETWEEN, LINE_THROUGH},
+    consts::{
+        Flag, Piece, Rank, Right, Side, CASTLE_KING_FILE, CASTLE_ROOK_FILE, IN_BETWEEN,
+        LINE_THROUGH,
+    },
     position::{Move, Position},
 };
 
@@ -11,8 +14,11 @@ macro_rules! pop_lsb {
     };
 }
 
+/// Fixed capacity list of moves, only the first `len` entries of `list` are valid.
 pub struct MoveList {
+    /// Moves, followed by unused entries.
     pub list: [Move; 252],
+    /// Number of moves.
     pub len: usize,
 }
 
@@ -34,6 +40,7 @@ fn encode<const PC: usize, const FLAG: u8>(moves: &mut MoveList, mut attacks: u6
 }
 
 impl Position {
+    /// Generates all legal moves in the position.
     #[must_use]
     pub fn gen(&self) -> MoveList {
         let mut moves = MoveList {
@@ -50,7 +57,7 @@ impl Position {
         if checkers == 0 {
             self.gen_pnbrq(&mut moves, u64::MAX, u64::MAX, pinned);
             self.castles(&mut moves, self.occ());
-        } else if checkers & (checkers - 1) == 0 {
+        } else if checkers.is_power_of_two() {
             let checker_sq = checkers.trailing_zeros() as usize;
             let free = IN_BETWEEN[king_sq][checker_sq];
             self.gen_pnbrq(&mut moves, checkers, free, pinned);
@@ -115,37 +122,53 @@ impl Position {
     }
 
     fn castles(&self, moves: &mut MoveList, occ: u64) {
-        if self.stm() == Side::BLACK {
-            if self.can_castle::<{ Side::BLACK }, 0>(occ, 59, 58) {
-                moves.push(60, 58, Flag::QS, Piece::KING);
-            }
-            if self.can_castle::<{ Side::BLACK }, 1>(occ, 61, 62) {
-                moves.push(60, 62, Flag::KS, Piece::KING);
-            }
-        } else {
-            if self.can_castle::<{ Side::WHITE }, 0>(occ, 3, 2) {
-                moves.push(4, 2, Flag::QS, Piece::KING);
-            }
-            if self.can_castle::<{ Side::WHITE }, 1>(occ, 5, 6) {
-                moves.push(4, 6, Flag::KS, Piece::KING);
+        let side = self.stm();
+        let king_sq = self.king_index();
+
+        for (ks, flag) in [(0, Flag::QS), (1, Flag::KS)] {
+            if self.can_castle(occ, side, ks) {
+                let rook_sq = self.castling_rook(side, ks);
+                moves.push(king_sq as u8, rook_sq, flag, Piece::KING);
             }
         }
     }
 
-    fn can_castle<const SIDE: usize, const KS: usize>(
-        &self,
-        occ: u64,
-        sq1: usize,
-        sq2: usize,
-    ) -> bool {
-        self.rights() & Right::TABLE[SIDE][KS] > 0
-            && occ & Path::TABLE[SIDE][KS] == 0
-            && !self.is_square_attacked(sq1, SIDE, occ)
-            && !self.is_square_attacked(sq2, SIDE, occ)
+    // in Chess960 the king and rook may start anywhere on the back rank,
+    // so the squares they cross are found between their start and end squares
+    fn can_castle(&self, occ: u64, side: usize, ks: usize) -> bool {
+        if self.rights() & Right::TABLE[side][ks] == 0 {
+            return false;
+        }
+
+        let king_sq = self.king_index();
+        let rook_sq = usize::from(self.castling_rook(side, ks));
+        let rank = king_sq & 0x38;
+        let king_to = rank | usize::from(CASTLE_KING_FILE[ks]);
+        let rook_to = rank | usize::from(CASTLE_ROOK_FILE[ks]);
+
+        let king_path = IN_BETWEEN[king_sq][king_to] | (1 << king_to);
+        let rook_path = IN_BETWEEN[rook_sq][rook_to] | (1 << rook_to);
+        let castlers = (1 << king_sq) | (1 << rook_sq);
+        if occ & !castlers & (king_path | rook_path) > 0 {
+            return false;
+        }
+
+        // the rook may be shielding a square the king crosses
+        let occ = occ ^ (1 << rook_sq);
+        let mut path = king_path;
+        while path > 0 {
+            pop_lsb!(sq, path);
+            if self.is_square_attacked(usize::from(sq), side, occ) {
+                return false;
+            }
+        }
+
+        true
     }
 
+    /// Pieces giving check to the side to move.
     #[must_use]
-    fn checkers(&self) -> u64 {
+    pub fn checkers(&self) -> u64 {
         self.attackers_to_square(self.king_index(), self.stm(), self.occ())
     }
 
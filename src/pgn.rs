@@ -0,0 +1,270 @@
+use super::{
+    position::{FenError, Position},
+    STARTPOS,
+};
+
+/// Replays every game in a PGN file, giving `(game, ply, position)` for the starting
+/// position of each game and the position after each of its moves, games and plies
+/// counting from 0.
+///
+/// Tags other than `FEN` are ignored, as are comments, variations and NAGs. After an
+/// error the rest of the game is skipped and reading carries on with the next one.
+pub struct PgnReader<'a> {
+    text: &'a str,
+    pending: Option<Token<'a>>,
+    game: usize,
+    ply: usize,
+    fen: Option<&'a str>,
+    // current position, once the movetext of the game has started
+    pos: Option<Position>,
+    skipping: bool,
+}
+
+#[derive(Clone, Copy)]
+enum Token<'a> {
+    Tag(&'a str, &'a str),
+    Move(&'a str),
+    Result,
+}
+
+impl<'a> PgnReader<'a> {
+    /// Reads games from the text of a PGN file.
+    #[must_use]
+    pub fn new(text: &'a str) -> Self {
+        Self {
+            text,
+            pending: None,
+            game: 0,
+            ply: 0,
+            fen: None,
+            pos: None,
+            skipping: false,
+        }
+    }
+
+    fn error(&mut self, ply: usize, kind: PgnErrorKind) -> PgnError {
+        self.skipping = true;
+        PgnError {
+            game: self.game,
+            ply,
+            kind,
+        }
+    }
+
+    fn end_game(&mut self) {
+        self.game += 1;
+        self.fen = None;
+        self.pos = None;
+        self.skipping = false;
+    }
+
+    // nothing after an unclosed bracket can be read
+    fn unterminated(&mut self, what: &'static str) -> PgnErrorKind {
+        self.text = "";
+        PgnErrorKind::Unterminated(what)
+    }
+
+    fn next_token(&mut self) -> Result<Option<Token<'a>>, PgnErrorKind> {
+        loop {
+            let text = self.text.trim_start();
+            let Some(first) = text.chars().next() else {
+                self.text = text;
+                return Ok(None);
+            };
+
+            let (token, len) = match first {
+                '[' => {
+                    let len = closing(text, '[', ']').ok_or_else(|| self.unterminated("tag"))?;
+                    (Some(parse_tag(&text[1..len - 1])), len)
+                }
+                '{' => (
+                    None,
+                    text.find('}').ok_or_else(|| self.unterminated("comment"))? + 1,
+                ),
+                '(' => (
+                    None,
+                    closing(text, '(', ')').ok_or_else(|| self.unterminated("variation"))?,
+                ),
+                // line comments and escaped lines
+                ';' | '%' => (None, text.find('\n').unwrap_or(text.len())),
+                '*' => (Some(Token::Result), 1),
+                '$' => (
+                    None,
+                    1 + text[1..]
+                        .find(|ch: char| !ch.is_ascii_digit())
+                        .unwrap_or(text.len() - 1),
+                ),
+                ')' | ']' | '}' => {
+                    self.text = &text[1..];
+                    return Err(PgnErrorKind::Unexpected(first));
+                }
+                _ => {
+                    let len = text
+                        .find(|ch: char| ch.is_whitespace() || "[]{}();$".contains(ch))
+                        .unwrap_or(text.len());
+                    (parse_word(&text[..len]), len)
+                }
+            };
+
+            self.text = &text[len..];
+            if token.is_some() {
+                return Ok(token);
+            }
+        }
+    }
+}
+
+impl Iterator for PgnReader<'_> {
+    type Item = Result<(usize, usize, Position), PgnError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let next = match self.pending.take() {
+                Some(token) => Ok(Some(token)),
+                None => self.next_token(),
+            };
+
+            let token = match next {
+                Ok(Some(token)) => token,
+                Ok(None) => {
+                    if self.pos.is_some() {
+                        self.end_game();
+                    }
+                    return None;
+                }
+                Err(kind) => {
+                    let ply = self.ply;
+                    return Some(Err(self.error(ply, kind)));
+                }
+            };
+
+            match token {
+                Token::Tag(name, value) => {
+                    if self.pos.is_some() || self.skipping {
+                        self.end_game();
+                    }
+                    if name == "FEN" {
+                        self.fen = Some(value);
+                    }
+                }
+                Token::Move(_) | Token::Result if self.pos.is_none() && !self.skipping => {
+                    self.pending = Some(token);
+                    self.ply = 0;
+
+                    match Position::from_fen(self.fen.unwrap_or(STARTPOS)) {
+                        Ok(pos) => {
+                            self.pos = Some(pos);
+                            return Some(Ok((self.game, 0, pos)));
+                        }
+                        Err(err) => return Some(Err(self.error(0, PgnErrorKind::Fen(err)))),
+                    }
+                }
+                Token::Result => self.end_game(),
+                Token::Move(_) if self.skipping => {}
+                Token::Move(san) => {
+                    let pos = self.pos.as_mut().expect("movetext started");
+                    let Some(mov) = pos.parse_san(san) else {
+                        let ply = self.ply + 1;
+                        return Some(Err(self.error(ply, PgnErrorKind::IllegalMove(san.into()))));
+                    };
+
+                    pos.make(mov);
+                    self.ply += 1;
+                    return Some(Ok((self.game, self.ply, *pos)));
+                }
+            }
+        }
+    }
+}
+
+// length of the text up to and including the bracket
+// matching its first character, skipping over comments
+fn closing(text: &str, open: char, close: char) -> Option<usize> {
+    let mut depth = 0;
+    let mut in_comment = false;
+    let mut in_string = false;
+
+    for (idx, ch) in text.char_indices() {
+        match ch {
+            '"' if open == '[' => in_string = !in_string,
+            '{' if !in_string => in_comment = true,
+            '}' if !in_string => in_comment = false,
+            _ if in_comment || in_string => {}
+            _ if ch == open => depth += 1,
+            _ if ch == close => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(idx + 1);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
+// a tag such as `White "Carlsen, Magnus"`, with the quotes removed from the value
+fn parse_tag(tag: &str) -> Token<'_> {
+    let tag = tag.trim();
+    let (name, value) = tag.split_once(char::is_whitespace).unwrap_or((tag, ""));
+    let value = value.trim();
+    let value = value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .unwrap_or(value);
+    Token::Tag(name, value)
+}
+
+// a move, a result or a move number, which may run into the move as in `1.e4`
+fn parse_word(word: &str) -> Option<Token<'_>> {
+    if matches!(word, "1-0" | "0-1" | "1/2-1/2") {
+        return Some(Token::Result);
+    }
+
+    let san = if word.starts_with(|ch: char| ch.is_ascii_digit()) && word.contains('.') {
+        word.trim_start_matches(|ch: char| ch.is_ascii_digit() || ch == '.')
+    } else {
+        word
+    };
+
+    (!san.is_empty()).then_some(Token::Move(san))
+}
+
+/// A problem found while reading a PGN file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PgnError {
+    /// Index of the game, counting from 0.
+    pub game: usize,
+    /// The ply the error happened at, a bad move on ply 1 is the first move of the game.
+    pub ply: usize,
+    /// What went wrong.
+    pub kind: PgnErrorKind,
+}
+
+/// Reasons a PGN game can fail to be read.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PgnErrorKind {
+    /// The `FEN` tag is invalid.
+    Fen(FenError),
+    /// A move that is not legal, or not written in valid SAN.
+    IllegalMove(String),
+    /// A tag, comment or variation that is never closed.
+    Unterminated(&'static str),
+    /// A closing bracket with no opening one.
+    Unexpected(char),
+}
+
+impl std::fmt::Display for PgnError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "game {}, ply {}: ", self.game + 1, self.ply)?;
+        match &self.kind {
+            PgnErrorKind::Fen(err) => write!(f, "invalid fen: {err}"),
+            PgnErrorKind::IllegalMove(san) => write!(f, "illegal move '{san}'"),
+            PgnErrorKind::Unterminated(what) => write!(f, "unterminated {what}"),
+            PgnErrorKind::Unexpected(ch) => write!(f, "unexpected '{ch}'"),
+        }
+    }
+}
+
+impl std::error::Error for PgnError {}
@@ -1,16 +1,30 @@
 use super::{
     attacks::Attacks,
-    consts::{Flag, Piece, Right, Side, CASTLE_MASK, ROOK_MOVES},
+    consts::{Flag, Piece, Right, Side, CASTLE_KING_FILE, CASTLE_ROOK_FILE, ZVALS},
 };
 
+// squares of the castling rooks in standard chess
+const CORNERS: [[u8; 2]; 2] = [[0, 7], [56, 63]];
+
+/// A chess position, cheap to copy.
+///
+/// Bitboards are indexed by the constants in [`Side`] and [`Piece`],
+/// squares are numbered from a1 = 0 to h8 = 63.
 #[derive(Copy, Clone, Default)]
 pub struct Position {
     bb: [u64; 8],
     stm: bool,
     enp_sq: u8,
     rights: u8,
+    rooks: [[u8; 2]; 2],
+    castlers: u64,
+    chess960: bool,
+    halfm: u16,
+    fullm: u16,
+    hash: u64,
 }
 
+/// A move as produced by [`Position::gen`].
 #[derive(Copy, Clone, Default)]
 pub struct Move {
     from: u8,
@@ -20,6 +34,7 @@ pub struct Move {
 }
 
 impl Move {
+    /// Creates a move of piece `moved` between two squares, `flag` being one of [`Flag`].
     #[must_use]
     pub fn new(from: u8, to: u8, flag: u8, moved: u8) -> Self {
         Self {
@@ -30,63 +45,158 @@ impl Move {
         }
     }
 
+    /// Square the piece moves from.
+    #[must_use]
+    pub fn from(self) -> u8 {
+        self.from
+    }
+
+    /// Square the piece moves to.
     #[must_use]
-    pub fn to_uci(self) -> String {
+    pub fn to(self) -> u8 {
+        self.to
+    }
+
+    /// One of the constants in [`Flag`].
+    #[must_use]
+    pub fn flag(self) -> u8 {
+        self.flag
+    }
+
+    /// The moving piece, one of the constants in [`Piece`].
+    #[must_use]
+    pub fn moved(self) -> usize {
+        usize::from(self.moved)
+    }
+
+    /// The move in UCI notation, such as `e2e4` or `e7e8q`.
+    ///
+    /// Castling is written as the king moving to the c or g file, or with
+    /// `chess960` as the king taking its own rook, such as `e1h1`.
+    #[must_use]
+    pub fn to_uci(self, chess960: bool) -> String {
         let idx_to_sq = |i| format!("{}{}", ((i & 7) + b'a') as char, (i / 8) + 1);
         let promo = if self.flag & 0b1000 > 0 {
             ["n", "b", "r", "q"][(self.flag & 0b11) as usize]
         } else {
             ""
         };
-        format!("{}{}{}", idx_to_sq(self.from), idx_to_sq(self.to), promo)
+        let to = if self.is_castle() && !chess960 {
+            self.castle_squares().0
+        } else {
+            self.to
+        };
+        format!("{}{}{}", idx_to_sq(self.from), idx_to_sq(to), promo)
+    }
+
+    /// Whether the move castles, in which case [`Move::to`] is the rook's square.
+    #[must_use]
+    pub fn is_castle(self) -> bool {
+        self.flag == Flag::KS || self.flag == Flag::QS
+    }
+
+    // squares the king and rook land on when castling
+    pub(crate) fn castle_squares(self) -> (u8, u8) {
+        let rank = self.from & 0x38;
+        let ks = usize::from(self.flag == Flag::KS);
+        (rank | CASTLE_KING_FILE[ks], rank | CASTLE_ROOK_FILE[ks])
     }
 }
 
 impl Position {
     // ACCESSOR METHODS
 
+    /// Bitboard of the given [`Side`] or [`Piece`].
     #[must_use]
     pub fn piece(&self, piece: usize) -> u64 {
         self.bb[piece]
     }
 
+    /// The [`Side`] to move.
     #[must_use]
     pub fn stm(&self) -> usize {
         usize::from(self.stm)
     }
 
+    /// Castling rights, as bits from [`Right`].
     #[must_use]
     pub fn rights(&self) -> u8 {
         self.rights
     }
 
+    /// Square of the rook `side` castles with, on the kingside if `ks` is 1.
+    #[must_use]
+    pub fn castling_rook(&self, side: usize, ks: usize) -> u8 {
+        self.rooks[side][ks]
+    }
+
+    /// Whether castling is written in Chess960 style, set by [`Position::from_fen`]
+    /// when the castling rooks are not on their usual squares.
+    #[must_use]
+    pub fn is_chess960(&self) -> bool {
+        self.chess960
+    }
+
+    /// Writes castling rights as rook files in FENs, and castling moves as
+    /// the king taking its own rook.
+    pub fn set_chess960(&mut self, chess960: bool) {
+        self.chess960 = chess960;
+    }
+
+    /// En passant target square, or 0 if there is none.
     #[must_use]
     pub fn enp_sq(&self) -> u8 {
         self.enp_sq
     }
 
+    /// Plies since the last capture or pawn move.
+    #[must_use]
+    pub fn halfmove_clock(&self) -> u16 {
+        self.halfm
+    }
+
+    /// Starts at 1 and is incremented after each move by black.
+    #[must_use]
+    pub fn fullmove_number(&self) -> u16 {
+        self.fullm
+    }
+
+    /// Zobrist hash of the position, updated incrementally by [`Position::make`].
+    ///
+    /// The en passant square is only part of it when a pawn can legally
+    /// take there, so positions that only differ by an unusable square are equal.
+    #[must_use]
+    pub fn hash(&self) -> u64 {
+        self.hash ^ self.enp_hash()
+    }
+
     // POSITION INFO
 
+    /// All occupied squares.
     #[must_use]
     pub fn occ(&self) -> u64 {
         self.bb[Side::WHITE] | self.bb[Side::BLACK]
     }
 
+    /// Square of the king of the side to move.
     #[must_use]
     pub fn king_index(&self) -> usize {
         (self.bb[Piece::KING] & self.bb[usize::from(self.stm)]).trailing_zeros() as usize
     }
 
+    /// Pieces of the side to move.
     #[must_use]
     pub fn boys(&self) -> u64 {
         self.bb[usize::from(self.stm)]
     }
 
+    /// Pieces of the side not to move.
     #[must_use]
     pub fn opps(&self) -> u64 {
         self.bb[usize::from(!self.stm)]
     }
 
+    /// Pieces of the opponent of `side` attacking `sq`, given the occupancy `occ`.
     #[must_use]
     pub fn attackers_to_square(&self, sq: usize, side: usize, occ: u64) -> u64 {
         ((Attacks::knight(sq) & self.bb[Piece::KNIGHT])
@@ -97,11 +207,33 @@ impl Position {
             & self.bb[side ^ 1]
     }
 
+    /// Whether any piece of the opponent of `side` attacks `sq`.
     #[must_use]
     pub fn is_square_attacked(&self, sq: usize, side: usize, occ: u64) -> bool {
         self.attackers_to_square(sq, side, occ) > 0
     }
 
+    /// Computes the Zobrist hash from scratch.
+    #[must_use]
+    pub fn compute_hash(&self) -> u64 {
+        let mut hash = 0;
+
+        for side in [Side::WHITE, Side::BLACK] {
+            for pc in Piece::PAWN..=Piece::KING {
+                hash ^= zobrist(side, pc, self.bb[side] & self.bb[pc]);
+            }
+        }
+
+        hash ^= ZVALS.castle[usize::from(self.rights)] ^ self.enp_hash();
+
+        if self.stm {
+            hash ^= ZVALS.side;
+        }
+
+        hash
+    }
+
+    /// The [`Piece`] on the square `bit`, kings and empty squares give [`Piece::EMPTY`].
     #[must_use]
     pub fn get_pc(&self, bit: u64) -> usize {
         for pc in Piece::PAWN..=Piece::QUEEN {
@@ -114,11 +246,12 @@ impl Position {
 
     // MODIFY POSITION
 
-    pub fn toggle(&mut self, side: usize, piece: usize, bit: u64) {
+    fn toggle(&mut self, side: usize, piece: usize, bit: u64) {
         self.bb[piece] ^= bit;
         self.bb[side] ^= bit;
     }
 
+    /// Plays a legal move generated for this position.
     pub fn make(&mut self, mov: Move) {
         // extracting move info
         let side = usize::from(self.stm);
@@ -130,88 +263,704 @@ impl Position {
             self.get_pc(bb_to)
         };
 
-        // updating state
+        // updating state, the hash only for what changes
+        let (from, to, moved) = (
+            usize::from(mov.from),
+            usize::from(mov.to),
+            usize::from(mov.moved),
+        );
+        let keys = &ZVALS.pieces;
+        self.hash ^= ZVALS.side;
+
         self.stm = !self.stm;
         self.enp_sq = 0;
-        self.rights &= CASTLE_MASK[usize::from(mov.to)] & CASTLE_MASK[usize::from(mov.from)];
+        self.fullm = self.fullm.saturating_add(u16::from(side == Side::BLACK));
+        self.halfm = if mov.moved == Piece::PAWN as u8 || captured != Piece::EMPTY {
+            0
+        } else {
+            self.halfm.saturating_add(1)
+        };
+        if (bb_from | bb_to) & self.castlers > 0 {
+            let rights = self.rights & self.castle_mask(mov, side);
+            self.hash ^= ZVALS.castle[usize::from(self.rights)] ^ ZVALS.castle[usize::from(rights)];
+            self.rights = rights;
+            self.castlers = self.castlers();
+        }
 
         // move piece
-        self.toggle(side, usize::from(mov.moved), bb_from ^ bb_to);
+        self.toggle(side, moved, bb_from ^ bb_to);
+        self.hash ^= keys[side][moved][from] ^ keys[side][moved][to];
 
         // captures
         if captured != Piece::EMPTY {
             self.toggle(side ^ 1, captured, bb_to);
+            self.hash ^= keys[side ^ 1][captured][to];
         }
 
         // more complex moves
         match mov.flag {
             Flag::DBL => self.enp_sq = mov.to ^ 8,
             Flag::KS | Flag::QS => {
-                let bits = ROOK_MOVES[usize::from(mov.flag == Flag::KS)][side];
-                self.toggle(side, Piece::ROOK, bits);
+                // the king was moved onto its rook's square above
+                let (king, rook) = mov.castle_squares();
+                let (king, rook) = (usize::from(king), usize::from(rook));
+                self.toggle(side, Piece::KING, bb_to ^ (1 << king));
+                self.toggle(side, Piece::ROOK, bb_to ^ (1 << rook));
+                let keys = &keys[side];
+                self.hash ^= keys[Piece::KING][to]
+                    ^ keys[Piece::KING][king]
+                    ^ keys[Piece::ROOK][to]
+                    ^ keys[Piece::ROOK][rook];
             }
             Flag::ENP => {
-                let bits = 1 << (mov.to ^ 8);
-                self.toggle(side ^ 1, Piece::PAWN, bits);
+                self.toggle(side ^ 1, Piece::PAWN, 1 << (to ^ 8));
+                self.hash ^= keys[side ^ 1][Piece::PAWN][to ^ 8];
             }
             Flag::NPR.. => {
                 let promo = usize::from((mov.flag & 3) + 3);
                 self.bb[Piece::PAWN] ^= bb_to;
                 self.bb[promo] ^= bb_to;
+                self.hash ^= keys[side][Piece::PAWN][to] ^ keys[side][promo][to];
             }
             _ => {}
         }
+
+        debug_assert_eq!(
+            self.hash(),
+            self.compute_hash(),
+            "incremental hash differs after {}",
+            mov.to_uci(self.chess960)
+        );
+    }
+
+    // the en passant part of the hash, which is left out of the
+    // incrementally updated hash and added by Position::hash
+    fn enp_hash(&self) -> u64 {
+        if self.enp_sq > 0 && self.can_capture_enp() {
+            ZVALS.enp[usize::from(self.enp_sq & 7)]
+        } else {
+            0
+        }
+    }
+
+    // whether a pawn of the side to move can legally take on the en passant square
+    fn can_capture_enp(&self) -> bool {
+        let side = usize::from(self.stm);
+        let enp = 1 << self.enp_sq;
+        let captured = 1 << (self.enp_sq ^ 8);
+        let king = self.king_index();
+        let mut pawns =
+            Attacks::pawn(usize::from(self.enp_sq), side ^ 1) & self.bb[Piece::PAWN] & self.boys();
+
+        while pawns > 0 {
+            let occ = self.occ() ^ (pawns & pawns.wrapping_neg()) ^ enp ^ captured;
+            if self.attackers_to_square(king, side, occ) & !captured == 0 {
+                return true;
+            }
+            pawns &= pawns - 1;
+        }
+
+        false
+    }
+
+    /// Same as [`Position::make`], but returns what is needed to take the move back
+    /// with [`Position::unmake`], for searching without copying the position.
+    pub fn make_undoable(&mut self, mov: Move) -> Undo {
+        let captured = if mov.flag & Flag::CAP == 0 || mov.flag == Flag::ENP {
+            Piece::EMPTY
+        } else {
+            self.get_pc(1 << mov.to)
+        };
+
+        let undo = Undo {
+            captured: captured as u8,
+            rights: self.rights,
+            enp_sq: self.enp_sq,
+            halfm: self.halfm,
+            fullm: self.fullm,
+            hash: self.hash,
+        };
+
+        self.make(mov);
+        undo
+    }
+
+    /// Takes back `mov`, which must be the last move played with [`Position::make_undoable`].
+    pub fn unmake(&mut self, mov: Move, undo: Undo) {
+        self.stm = !self.stm;
+        let side = usize::from(self.stm);
+        let bb_from = 1 << mov.from;
+        let bb_to = 1 << mov.to;
+
+        // every change made to the bitboards is an xor, so they are undone in any order
+        self.toggle(side, usize::from(mov.moved), bb_from ^ bb_to);
+
+        if undo.captured != Piece::EMPTY as u8 {
+            self.toggle(side ^ 1, usize::from(undo.captured), bb_to);
+        }
+
+        match mov.flag {
+            Flag::KS | Flag::QS => {
+                let (king, rook) = mov.castle_squares();
+                self.toggle(side, Piece::KING, bb_to ^ (1 << king));
+                self.toggle(side, Piece::ROOK, bb_to ^ (1 << rook));
+            }
+            Flag::ENP => self.toggle(side ^ 1, Piece::PAWN, 1 << (mov.to ^ 8)),
+            Flag::NPR.. => {
+                self.bb[Piece::PAWN] ^= bb_to;
+                self.bb[usize::from((mov.flag & 3) + 3)] ^= bb_to;
+            }
+            _ => {}
+        }
+
+        if self.rights != undo.rights {
+            self.rights = undo.rights;
+            self.castlers = self.castlers();
+        }
+        self.enp_sq = undo.enp_sq;
+        self.halfm = undo.halfm;
+        self.fullm = undo.fullm;
+        self.hash = undo.hash;
+    }
+
+    // kings and rooks that still have a castling right, the only
+    // squares a move has to touch to change the rights
+    fn castlers(&self) -> u64 {
+        let mut castlers = 0;
+        for (side, rights) in Right::TABLE.iter().enumerate() {
+            for (ks, &right) in rights.iter().enumerate() {
+                if self.rights & right > 0 {
+                    castlers |= (self.bb[side] & self.bb[Piece::KING]) | 1 << self.rooks[side][ks];
+                }
+            }
+        }
+        castlers
+    }
+
+    // rights kept after a move, lost by moving the king
+    // or by moving or capturing a castling rook
+    fn castle_mask(&self, mov: Move, side: usize) -> u8 {
+        let mut mask = if mov.moved == Piece::KING as u8 {
+            !(Right::TABLE[side][0] | Right::TABLE[side][1])
+        } else {
+            0b1111
+        };
+
+        for (rooks, rights) in self.rooks.iter().zip(Right::TABLE) {
+            for (&sq, right) in rooks.iter().zip(rights) {
+                if sq == mov.from || sq == mov.to {
+                    mask &= !right;
+                }
+            }
+        }
+
+        mask
+    }
+
+    /// Finds the legal move with the given UCI notation, castling
+    /// may be written either way described in [`Move::to_uci`].
+    #[must_use]
+    pub fn parse_uci_move(&self, uci: &str) -> Option<Move> {
+        let moves = self.gen();
+        let list = &moves.list[..moves.len];
+
+        // king takes rook first, as in Chess960 the other form
+        // can look the same as a plain king move
+        [true, false]
+            .into_iter()
+            .find_map(|chess960| list.iter().find(|mov| mov.to_uci(chess960) == uci))
+            .copied()
+    }
+
+    /// Plays a line of UCI moves such as `moves e2e4 e7e5`, the `moves` prefix being optional.
+    /// # Errors
+    /// Returns the first move that is not legal, in which case the position is left unchanged.
+    pub fn play_uci_moves(&mut self, line: &str) -> Result<(), IllegalMove> {
+        let mut tmp = *self;
+        for uci in line.split_whitespace().skip_while(|&word| word == "moves") {
+            let mov = tmp
+                .parse_uci_move(uci)
+                .ok_or_else(|| IllegalMove(uci.to_string()))?;
+            tmp.make(mov);
+        }
+
+        *self = tmp;
+        Ok(())
     }
 
     // CREATE POSITION
 
+    /// Parses a FEN string.
+    /// # Panics
+    /// Panics if the FEN is malformed, use [`Position::from_fen`] to handle errors.
     #[must_use]
     pub fn parse_fen(fen: &str) -> Self {
+        Self::from_fen(fen).unwrap_or_else(|err| panic!("invalid fen '{fen}': {err}"))
+    }
+
+    /// Parses a FEN string, the move clocks may be left out.
+    /// # Errors
+    /// Returns a [`FenError`] describing the first problem found in the FEN.
+    pub fn from_fen(fen: &str) -> Result<Self, FenError> {
         let mut pos = Self::default();
-        let vec: Vec<&str> = fen.split_whitespace().collect();
-        let p: Vec<char> = vec[0].chars().collect();
+        let mut fields = fen.split_whitespace();
+        let mut field = |name| fields.next().ok_or(FenError::MissingField(name));
 
         // board
-        let (mut row, mut col) = (7, 0);
-        for ch in p {
-            if ch == '/' {
-                row -= 1;
-                col = 0;
-            } else if ('1'..='8').contains(&ch) {
-                col += ch.to_string().parse::<i16>().unwrap_or(0);
-            } else {
-                let idx: usize = "PNBRQKpnbrqk"
-                    .chars()
-                    .position(|element| element == ch)
-                    .unwrap_or(6);
-                let colour = usize::from(idx > 5);
-                pos.toggle(colour, idx + 2 - 6 * colour, 1 << (8 * row + col));
-                col += 1;
+        let ranks: Vec<&str> = field("board")?.split('/').collect();
+        if ranks.len() != 8 {
+            return Err(FenError::RankCount(ranks.len()));
+        }
+
+        for (row, rank) in ranks.iter().rev().enumerate() {
+            let mut col = 0;
+            for ch in rank.chars() {
+                if col >= 8 {
+                    return Err(FenError::RankOverflow(row as u8 + 1));
+                }
+
+                if let Some(empty) = ch.to_digit(10).filter(|n| (1..=8).contains(n)) {
+                    col += empty as usize;
+                    if col > 8 {
+                        return Err(FenError::RankOverflow(row as u8 + 1));
+                    }
+                } else {
+                    let idx = "PNBRQKpnbrqk"
+                        .chars()
+                        .position(|element| element == ch)
+                        .ok_or(FenError::BadPiece(ch))?;
+                    let colour = usize::from(idx > 5);
+                    pos.toggle(colour, idx + 2 - 6 * colour, 1 << (8 * row + col));
+                    col += 1;
+                }
+            }
+
+            if col < 8 {
+                return Err(FenError::RankUnderflow(row as u8 + 1));
+            }
+        }
+
+        for side in [Side::WHITE, Side::BLACK] {
+            let count = (pos.bb[side] & pos.bb[Piece::KING]).count_ones();
+            if count != 1 {
+                return Err(FenError::KingCount { side, count });
+            }
+
+            // pieces beyond the starting set must have been pawns, which
+            // also keeps the legal moves within a move list
+            let pieces = |piece: usize| (pos.bb[side] & pos.bb[piece]).count_ones();
+            let promoted = (Piece::KNIGHT..=Piece::QUEEN)
+                .map(|piece| {
+                    pieces(piece).saturating_sub(if piece == Piece::QUEEN { 1 } else { 2 })
+                })
+                .sum::<u32>();
+            if pieces(Piece::PAWN) + promoted > 8 {
+                return Err(FenError::TooManyPieces(side));
             }
         }
 
         // side to move
-        pos.stm = vec[1] == "b";
+        pos.stm = match field("side to move")? {
+            "w" => false,
+            "b" => true,
+            stm => return Err(FenError::BadSideToMove(stm.to_string())),
+        };
 
-        // castle rights
-        for ch in vec[2].chars() {
-            pos.rights |= match ch {
-                'Q' => Right::WQS,
-                'K' => Right::WKS,
-                'q' => Right::BQS,
-                'k' => Right::BKS,
-                _ => 0,
+        let opp = usize::from(!pos.stm);
+        let opp_king = (pos.bb[opp] & pos.bb[Piece::KING]).trailing_zeros() as usize;
+        if pos.is_square_attacked(opp_king, opp, pos.occ()) {
+            return Err(FenError::OpponentInCheck);
+        }
+
+        // castle rights, as KQkq for the outermost rooks (X-FEN)
+        // or as the files of the castling rooks (Shredder-FEN)
+        pos.rooks = CORNERS;
+        let rights = field("castling rights")?;
+        if rights != "-" {
+            for ch in rights.chars() {
+                let (side, ks, rook) = pos.parse_castling(ch).ok_or(FenError::BadCastling(ch))?;
+                pos.rights |= Right::TABLE[side][ks];
+                pos.rooks[side][ks] = rook;
+                pos.chess960 |= ch.to_ascii_lowercase() != ['q', 'k'][ks];
             }
         }
 
+        // anything but kings on the e-file castling with corner rooks
+        for side in [Side::WHITE, Side::BLACK] {
+            let king = (pos.bb[side] & pos.bb[Piece::KING]).trailing_zeros();
+            let castles = pos.rights & (Right::TABLE[side][0] | Right::TABLE[side][1]) > 0;
+            pos.chess960 |= castles && king != [4, 60][side];
+        }
+        pos.chess960 |= pos.rooks != CORNERS;
+        pos.castlers = pos.castlers();
+
         // en passant square
-        pos.enp_sq = if vec[3] == "-" {
-            0
+        let enp = field("en passant square")?;
+        if enp != "-" {
+            pos.enp_sq = pos
+                .parse_enp(enp)
+                .ok_or_else(|| FenError::BadEnPassant(enp.to_string()))?;
+        }
+
+        // move clocks, which are often left out
+        let mut clock = |default| {
+            fields.next().map_or(Ok(default), |clock| {
+                clock
+                    .parse()
+                    .map_err(|_| FenError::BadMoveClock(clock.to_string()))
+            })
+        };
+        pos.halfm = clock(0)?;
+        pos.fullm = clock(1)?;
+
+        pos.hash = pos.compute_hash() ^ pos.enp_hash();
+
+        Ok(pos)
+    }
+
+    // side, kingside and rook square of a castling right,
+    // the rook must be on the back rank, on the given side of the king
+    fn parse_castling(&self, ch: char) -> Option<(usize, usize, u8)> {
+        let side = usize::from(ch.is_ascii_lowercase());
+        let back_rank = 0xFF << (56 * side);
+        let king = self.bb[side] & self.bb[Piece::KING] & back_rank;
+        let rooks = self.bb[side] & self.bb[Piece::ROOK] & back_rank;
+        if king == 0 {
+            return None;
+        }
+
+        let above_king = rooks & !(king | (king - 1));
+        let below_king = rooks & (king - 1);
+        let rook = match ch.to_ascii_lowercase() {
+            'k' if above_king > 0 => above_king.ilog2(),
+            'q' if below_king > 0 => below_king.trailing_zeros(),
+            file @ 'a'..='h' => {
+                let sq = 56 * side as u32 + u32::from(file) - u32::from('a');
+                if rooks & (1 << sq) == 0 {
+                    return None;
+                }
+                sq
+            }
+            _ => return None,
+        };
+
+        let ks = usize::from(1 << rook > king);
+        Some((side, ks, rook as u8))
+    }
+
+    // the target square must be on the 3rd/6th rank, be empty
+    // and lie behind a pawn that could have just double pushed
+    fn parse_enp(&self, enp: &str) -> Option<u8> {
+        let &[file @ b'a'..=b'h', rank @ b'1'..=b'8'] = enp.as_bytes() else {
+            return None;
+        };
+
+        if rank != [b'6', b'3'][usize::from(self.stm)] {
+            return None;
+        }
+
+        let sq = 8 * (rank - b'1') + file - b'a';
+        let pawn_sq = if self.stm { sq + 8 } else { sq - 8 };
+
+        let opp_pawns = self.opps() & self.bb[Piece::PAWN];
+        let valid = self.occ() & (1 << sq) == 0 && opp_pawns & (1 << pawn_sq) > 0;
+
+        valid.then_some(sq)
+    }
+
+    /// Writes the position as a FEN string.
+    #[must_use]
+    pub fn to_fen(&self) -> String {
+        let mut fen = String::new();
+
+        // board
+        for row in (0..8).rev() {
+            let mut empty = 0;
+            for col in 0..8 {
+                let bit = 1 << (8 * row + col);
+                if self.occ() & bit == 0 {
+                    empty += 1;
+                    continue;
+                }
+
+                if empty > 0 {
+                    fen.push_str(&empty.to_string());
+                    empty = 0;
+                }
+
+                let colour = usize::from(self.bb[Side::BLACK] & bit > 0);
+                let pc = (Piece::PAWN..=Piece::KING)
+                    .find(|&pc| self.bb[pc] & bit > 0)
+                    .unwrap_or(Piece::PAWN);
+                fen.push(b"PNBRQKpnbrqk"[pc - 2 + 6 * colour] as char);
+            }
+
+            if empty > 0 {
+                fen.push_str(&empty.to_string());
+            }
+
+            if row > 0 {
+                fen.push('/');
+            }
+        }
+
+        // side to move
+        fen.push_str([" w ", " b "][usize::from(self.stm)]);
+
+        // castle rights, as rook files in Chess960
+        if self.rights == 0 {
+            fen.push('-');
+        }
+        for (side, ks, ch) in [
+            (Side::WHITE, 1, 'K'),
+            (Side::WHITE, 0, 'Q'),
+            (Side::BLACK, 1, 'k'),
+            (Side::BLACK, 0, 'q'),
+        ] {
+            if self.rights & Right::TABLE[side][ks] == 0 {
+                continue;
+            }
+
+            if self.chess960 {
+                let file = (b'a' + (self.rooks[side][ks] & 7)) as char;
+                fen.push(if side == Side::WHITE {
+                    file.to_ascii_uppercase()
+                } else {
+                    file
+                });
+            } else {
+                fen.push(ch);
+            }
+        }
+
+        // en passant square
+        fen.push(' ');
+        if self.enp_sq > 0 {
+            fen.push((b'a' + (self.enp_sq & 7)) as char);
+            fen.push((b'1' + self.enp_sq / 8) as char);
         } else {
-            let chs: Vec<char> = vec[3].chars().collect();
-            8 * chs[1].to_string().parse::<u8>().unwrap_or(0) + chs[0] as u8 - 105
+            fen.push('-');
+        }
+
+        // move clocks
+        format!("{fen} {} {}", self.halfm, self.fullm)
+    }
+}
+
+impl std::fmt::Display for Position {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_fen())
+    }
+}
+
+/// State lost by playing a move, returned by [`Position::make_undoable`].
+#[derive(Copy, Clone, Debug)]
+pub struct Undo {
+    captured: u8,
+    rights: u8,
+    enp_sq: u8,
+    halfm: u16,
+    fullm: u16,
+    hash: u64,
+}
+
+/// A move that is not legal in the position it was played in.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IllegalMove(pub String);
+
+impl std::fmt::Display for IllegalMove {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "illegal move '{}'", self.0)
+    }
+}
+
+impl std::error::Error for IllegalMove {}
+
+/// Reasons a FEN string can be rejected by [`Position::from_fen`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FenError {
+    /// A required field is missing.
+    MissingField(&'static str),
+    /// The board does not have exactly 8 ranks.
+    RankCount(usize),
+    /// A rank describes more than 8 squares.
+    RankOverflow(u8),
+    /// A rank describes fewer than 8 squares.
+    RankUnderflow(u8),
+    /// A character that is not a piece or a digit from 1 to 8.
+    BadPiece(char),
+    /// A side does not have exactly one king.
+    KingCount {
+        /// The [`Side`] with the wrong number of kings.
+        side: usize,
+        /// How many kings it has.
+        count: u32,
+    },
+    /// The side to move is not `w` or `b`.
+    BadSideToMove(String),
+    /// An unknown castling right.
+    BadCastling(char),
+    /// The en passant square is malformed or impossible in the position.
+    BadEnPassant(String),
+    /// A move clock is not a number.
+    BadMoveClock(String),
+    /// The side not to move is in check, so its king could be captured.
+    OpponentInCheck,
+    /// A [`Side`] has more pawns and promoted pieces than its 8 pawns.
+    TooManyPieces(usize),
+}
+
+impl std::fmt::Display for FenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingField(name) => write!(f, "missing {name}"),
+            Self::RankCount(count) => write!(f, "expected 8 ranks, found {count}"),
+            Self::RankOverflow(rank) => write!(f, "rank {rank} has more than 8 squares"),
+            Self::RankUnderflow(rank) => write!(f, "rank {rank} has fewer than 8 squares"),
+            Self::BadPiece(ch) => write!(f, "invalid piece '{ch}'"),
+            Self::KingCount { side, count } => {
+                let colour = ["white", "black"][*side];
+                write!(f, "expected one {colour} king, found {count}")
+            }
+            Self::BadSideToMove(stm) => write!(f, "invalid side to move '{stm}'"),
+            Self::BadCastling(ch) => write!(f, "invalid castling right '{ch}'"),
+            Self::BadEnPassant(enp) => write!(f, "impossible en passant square '{enp}'"),
+            Self::BadMoveClock(clock) => write!(f, "invalid move clock '{clock}'"),
+            Self::OpponentInCheck => write!(f, "the side not to move is in check"),
+            Self::TooManyPieces(side) => {
+                let colour = ["white", "black"][*side];
+                write!(f, "too many {colour} pieces to have come from its pawns")
+            }
+        }
+    }
+}
+
+impl std::error::Error for FenError {}
+
+fn zobrist(side: usize, piece: usize, mut bits: u64) -> u64 {
+    let mut hash = 0;
+    while bits > 0 {
+        hash ^= ZVALS.pieces[side][piece][bits.trailing_zeros() as usize];
+        bits &= bits - 1;
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Position;
+
+    #[test]
+    fn fen_round_trip() {
+        let suites = [
+            include_str!("../perft_results.txt"),
+            include_str!("../perft_960.txt"),
+        ];
+        for line in suites.iter().flat_map(|suite| suite.lines()) {
+            let fen = line.split(';').next().unwrap().trim();
+            assert_eq!(Position::parse_fen(fen).to_fen(), fen);
+        }
+    }
+
+    #[test]
+    fn unusable_en_passant_square() {
+        // the square is kept, but only hashed when a pawn can take there
+        let hash = |fen| Position::parse_fen(fen).hash();
+
+        // taking would open the rank between the rook and the king
+        let pinned = Position::parse_fen("4k3/8/8/1rpP2K1/8/8/8/8 w - c6 0 1");
+        assert_eq!(pinned.enp_sq(), 42);
+        assert_eq!(pinned.hash(), hash("4k3/8/8/1rpP2K1/8/8/8/8 w - - 0 1"));
+
+        let mut pos = Position::parse_fen("4k3/2p5/8/3P4/8/8/8/4K3 b - - 0 1");
+        pos.play_uci_moves("c7c5").unwrap();
+        assert_eq!(pos.to_fen(), "4k3/8/8/2pP4/8/8/8/4K3 w - c6 0 2");
+        assert_ne!(pos.hash(), hash("4k3/8/8/2pP4/8/8/8/4K3 w - - 0 2"));
+
+        let mut pos = Position::parse_fen("4k3/2p5/8/8/8/8/8/4K3 b - - 0 1");
+        pos.play_uci_moves("c7c5").unwrap();
+        assert_eq!(pos.to_fen(), "4k3/8/8/2p5/8/8/8/4K3 w - c6 0 2");
+        assert_eq!(pos.hash(), hash("4k3/8/8/2p5/8/8/8/4K3 w - - 0 2"));
+    }
+
+    #[test]
+    fn move_clocks() {
+        let mut pos = Position::parse_fen("4k3/4p3/8/8/8/8/8/R3K3 w - - 7 20");
+        let mut play = |uci: &str| {
+            let moves = pos.gen();
+            let mov = moves.list[..moves.len]
+                .iter()
+                .find(|mov| mov.to_uci(false) == uci)
+                .unwrap();
+            pos.make(*mov);
+            (pos.halfmove_clock(), pos.fullmove_number())
         };
 
-        pos
+        assert_eq!(play("a1a7"), (8, 20));
+        assert_eq!(play("e8d8"), (9, 21));
+        assert_eq!(play("a7e7"), (0, 21));
+        assert_eq!(play("d8e7"), (0, 22));
+    }
+
+    #[test]
+    fn move_clocks_saturate() {
+        let mut pos = Position::parse_fen("4k3/8/8/8/8/8/8/4K3 b - - 65535 65535");
+        let mov = pos.parse_uci_move("e8d8").unwrap();
+        let undo = pos.make_undoable(mov);
+        assert_eq!(
+            (pos.halfmove_clock(), pos.fullmove_number()),
+            (65535, 65535)
+        );
+
+        pos.unmake(mov, undo);
+        assert_eq!(pos.to_fen(), "4k3/8/8/8/8/8/8/4K3 b - - 65535 65535");
+    }
+
+    #[test]
+    fn uci_moves() {
+        let mut pos = Position::parse_fen("r3k3/1P6/8/8/8/8/8/4K2R w Kq - 0 1");
+
+        assert!(pos.parse_uci_move("b7b8").is_none());
+        assert!(pos.parse_uci_move("e1c1").is_none());
+        assert_eq!(pos.parse_uci_move("b7a8n").unwrap().to_uci(false), "b7a8n");
+
+        let before = pos.to_fen();
+        let err = pos.play_uci_moves("moves e1g1 a8a7 b7b8").unwrap_err();
+        assert_eq!(err.0, "b7b8");
+        assert_eq!(pos.to_fen(), before);
+
+        pos.play_uci_moves("moves e1g1 a8a7 b7b8q").unwrap();
+        assert_eq!(pos.to_fen(), "1Q2k3/r7/8/8/8/8/8/5RK1 b - - 0 2");
+    }
+
+    #[test]
+    fn chess960_castling() {
+        let fen = "r3k1r1/8/8/8/8/8/8/RK5R w HAga - 0 1";
+        let mut pos = Position::parse_fen(fen);
+        assert!(pos.is_chess960());
+        assert_eq!(pos.to_fen(), fen);
+        assert_eq!(
+            Position::parse_fen("r3k1r1/8/8/8/8/8/8/RK5R w KQkq - 0 1").to_fen(),
+            fen
+        );
+
+        // the king stepping to c1 and castling queenside look the same in standard notation
+        let castle = pos.parse_uci_move("b1a1").unwrap();
+        assert!(castle.is_castle());
+        assert_eq!(castle.to_uci(false), "b1c1");
+        assert!(!pos.parse_uci_move("b1c1").unwrap().is_castle());
+
+        pos.make(castle);
+        assert_eq!(pos.to_fen(), "r3k1r1/8/8/8/8/8/8/2KR3R b ga - 1 1");
+
+        pos.play_uci_moves("e8g8").unwrap();
+        assert_eq!(pos.to_fen(), "r4rk1/8/8/8/8/8/8/2KR3R w - - 2 2");
+
+        let mut standard = Position::parse_fen(crate::STARTPOS);
+        standard.set_chess960(true);
+        assert_eq!(
+            standard.to_fen(),
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w HAha - 0 1"
+        );
     }
 }
@@ -0,0 +1,81 @@
+use super::position::Position;
+use std::time::{Duration, Instant};
+
+/// How far a perft run has got, given after each root move.
+#[derive(Clone, Copy, Debug)]
+pub struct Progress {
+    /// Root moves whose subtrees have been counted.
+    pub done: usize,
+    /// Number of root moves.
+    pub total: usize,
+    /// Leaf nodes counted so far.
+    pub nodes: u64,
+    /// Time since the run started.
+    pub elapsed: Duration,
+}
+
+impl Progress {
+    /// Leaf nodes counted per second so far.
+    #[must_use]
+    pub fn nps(&self) -> f64 {
+        self.nodes as f64 / self.elapsed.as_secs_f64()
+    }
+
+    /// Time left if the remaining root moves take as long on average as the ones done.
+    #[must_use]
+    pub fn eta(&self) -> Duration {
+        if self.done == 0 {
+            return Duration::ZERO;
+        }
+
+        let remaining = (self.total - self.done) as f64;
+        self.elapsed.mul_f64(remaining / self.done as f64)
+    }
+}
+
+/// The root loop of [`perft`](crate::perft), with `count` giving the number of leaves
+/// below each root move, such as `perft::<false, true>`, and
+/// `progress` called after each one. With `ROOT` the counts are printed as in `perft`.
+/// # Panics
+/// Panics if `depth` is 0, as there are no root moves to report on.
+pub fn perft_with_progress<const ROOT: bool, C, P>(
+    pos: &Position,
+    depth: u8,
+    mut count: C,
+    mut progress: P,
+) -> u64
+where
+    C: FnMut(&Position, u8) -> u64,
+    P: FnMut(&Progress),
+{
+    assert!(depth > 0, "perft_with_progress needs a depth of at least 1");
+
+    let start = Instant::now();
+    let moves = pos.gen();
+    let mut positions = 0;
+
+    for m_idx in 0..moves.len {
+        let mut tmp = *pos;
+        tmp.make(moves.list[m_idx]);
+
+        let num = if depth == 1 {
+            1
+        } else {
+            count(&tmp, depth - 1)
+        };
+        positions += num;
+
+        if ROOT {
+            println!("{}: {num}", moves.list[m_idx].to_uci(pos.is_chess960()));
+        }
+
+        progress(&Progress {
+            done: m_idx + 1,
+            total: moves.len,
+            nodes: positions,
+            elapsed: start.elapsed(),
+        });
+    }
+
+    positions
+}
@@ -0,0 +1,199 @@
+use super::{
+    consts::{xorshift, Flag, Piece},
+    perft,
+    position::{Move, Position},
+};
+use std::fmt::Write;
+
+/// Settings for [`RandomPositions`].
+///
+/// Each game draws which kinds of move it looks for with the percentages.
+/// Games that can't reach a suitable position by `max_ply` are abandoned
+/// and the next game draws again, so kinds that are hard to reach turn up
+/// in fewer positions than their percentage.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RandomSettings {
+    /// Fewest plies played from the starting position.
+    pub min_ply: u16,
+    /// Most plies played from the starting position.
+    pub max_ply: u16,
+    /// Fewest pieces left on the board, kings and pawns included.
+    pub min_pieces: u32,
+    /// Most pieces left on the board, kings and pawns included.
+    pub max_pieces: u32,
+    /// Chance in percent that a game looks for an en passant capture.
+    pub en_passant: u8,
+    /// Chance in percent that a game looks for a promotion.
+    pub promotion: u8,
+    /// Chance in percent that a game looks for a castling move.
+    pub castling: u8,
+}
+
+impl Default for RandomSettings {
+    fn default() -> Self {
+        Self {
+            min_ply: 10,
+            max_ply: 80,
+            min_pieces: 2,
+            max_pieces: 32,
+            en_passant: 10,
+            promotion: 10,
+            castling: 10,
+        }
+    }
+}
+
+/// Positions reached by playing random legal moves from a starting
+/// position, the same seed always giving the same positions.
+///
+/// Take as many positions as are needed. Ends early if
+/// [`MAX_ABANDONED`] games in a row are abandoned, as the settings
+/// probably can't be met from the starting position.
+/// ```
+/// use perft::{random::{RandomPositions, RandomSettings}, Position, STARTPOS};
+///
+/// let start = Position::parse_fen(STARTPOS);
+/// let suite = RandomPositions::new(start, 1, RandomSettings::default()).take(5);
+/// assert_eq!(suite.count(), 5);
+/// ```
+pub struct RandomPositions {
+    start: Position,
+    settings: RandomSettings,
+    seed: u64,
+}
+
+/// Games in a row that [`RandomPositions`] abandons before giving up.
+pub const MAX_ABANDONED: u32 = 100_000;
+
+// kinds of moves a game is trying to make available
+#[derive(Clone, Copy)]
+struct Wanted {
+    en_passant: bool,
+    promotion: bool,
+    castling: bool,
+}
+
+impl RandomPositions {
+    /// Plays games from `start`, which must have at least one legal move.
+    ///
+    /// # Panics
+    /// Panics if `min_ply` is greater than `max_ply`, or `min_pieces` than `max_pieces`.
+    #[must_use]
+    pub fn new(start: Position, seed: u64, settings: RandomSettings) -> Self {
+        assert!(settings.min_ply <= settings.max_ply, "empty ply range");
+        assert!(
+            settings.min_pieces <= settings.max_pieces,
+            "empty piece count range"
+        );
+        Self {
+            start,
+            settings,
+            // xorshift gets stuck on zero
+            seed: (seed ^ 0x9E37_79B9_7F4A_7C15).max(1),
+        }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.seed = xorshift(self.seed);
+        self.seed
+    }
+
+    // uniform enough in 0..n for small n
+    fn below(&mut self, n: u64) -> u64 {
+        self.next_u64() % n
+    }
+
+    fn chance(&mut self, percent: u8) -> bool {
+        self.below(100) < u64::from(percent)
+    }
+
+    fn game(&mut self) -> Option<Position> {
+        let RandomSettings {
+            min_ply, max_ply, ..
+        } = self.settings;
+        let wanted = Wanted {
+            en_passant: self.chance(self.settings.en_passant),
+            promotion: self.chance(self.settings.promotion),
+            castling: self.chance(self.settings.castling),
+        };
+        let target = min_ply + self.below(u64::from(max_ply - min_ply) + 1) as u16;
+
+        let mut pos = self.start;
+        for ply in 0..=max_ply {
+            let moves = pos.gen();
+            let moves = &moves.list[..moves.len];
+            if moves.is_empty() {
+                return None;
+            }
+
+            if ply >= target && self.suitable(&pos, moves, wanted) {
+                return Some(pos);
+            }
+
+            pos.make(self.pick(&pos, moves, wanted));
+        }
+
+        None
+    }
+
+    fn suitable(&self, pos: &Position, moves: &[Move], wanted: Wanted) -> bool {
+        let pieces = pos.occ().count_ones();
+        let has = |kind: fn(Move) -> bool| moves.iter().any(|&mov| kind(mov));
+
+        (self.settings.min_pieces..=self.settings.max_pieces).contains(&pieces)
+            && (!wanted.en_passant || has(|mov| mov.flag() == Flag::ENP))
+            && (!wanted.promotion || has(|mov| mov.flag() & Flag::NPR > 0))
+            && (!wanted.castling || has(Move::is_castle))
+    }
+
+    // weights moves towards the position the game is looking for: captures
+    // while there are too many pieces, pawn moves for en passant and
+    // promotions, and keeping the king and rooks still for castling
+    fn pick(&mut self, pos: &Position, moves: &[Move], wanted: Wanted) -> Move {
+        let crowded = pos.occ().count_ones() > self.settings.max_pieces;
+        let weight = |mov: Move| {
+            let mut weight = 4;
+            if crowded && mov.flag() & Flag::CAP > 0 {
+                weight *= 4;
+            }
+            if (wanted.en_passant || wanted.promotion) && mov.moved() == Piece::PAWN {
+                weight *= 4;
+            }
+            if wanted.castling && matches!(mov.moved(), Piece::KING | Piece::ROOK) {
+                weight /= 4;
+            }
+            weight
+        };
+
+        let total = moves.iter().map(|&mov| weight(mov)).sum::<u64>();
+        let mut roll = self.below(total);
+        for &mov in moves {
+            if roll < weight(mov) {
+                return mov;
+            }
+            roll -= weight(mov);
+        }
+
+        moves[0]
+    }
+}
+
+impl Iterator for RandomPositions {
+    type Item = Position;
+
+    fn next(&mut self) -> Option<Position> {
+        (0..MAX_ABANDONED).find_map(|_| self.game())
+    }
+}
+
+/// Writes `pos` as an EPD line with its perft counts from depth 1 to
+/// `depth`, in the format of `perft_results.txt`.
+#[must_use]
+pub fn to_epd(pos: &Position, depth: u8) -> String {
+    let mut line = pos.to_fen();
+    for d in 1..=depth {
+        // writing to a string can't fail
+        let _ = write!(line, "; D{d} {}", perft::<false, true>(pos, d));
+    }
+    line
+}
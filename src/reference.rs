@@ -0,0 +1,275 @@
+use super::{
+    consts::{Flag, Piece, Right, Side},
+    position::{Move, Position},
+};
+
+// (file, rank) steps for each piece, walked until blocked for sliders
+const KNIGHT: [(i8, i8); 8] = [
+    (1, 2),
+    (2, 1),
+    (2, -1),
+    (1, -2),
+    (-1, -2),
+    (-2, -1),
+    (-2, 1),
+    (-1, 2),
+];
+const KING: [(i8, i8); 8] = [
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+];
+const ROOK: [(i8, i8); 4] = [(1, 0), (0, 1), (-1, 0), (0, -1)];
+const BISHOP: [(i8, i8); 4] = [(1, 1), (-1, 1), (-1, -1), (1, -1)];
+
+const PROMOTIONS: [u8; 4] = [Flag::NPR, Flag::BPR, Flag::RPR, Flag::QPR];
+
+// the side and piece on each square
+type Board = [Option<(usize, usize)>; 64];
+
+/// Every legal move in `pos`, found by walking a mailbox board square by
+/// square and keeping the moves that don't leave the king attacked.
+///
+/// Much slower than [`Position::gen`], but simple enough to check it against.
+#[must_use]
+pub fn legal_moves(pos: &Position) -> Vec<Move> {
+    let side = pos.stm();
+    pseudo_legal(pos)
+        .into_iter()
+        .filter(|&mov| {
+            let mut tmp = *pos;
+            tmp.make(mov);
+            let board = board(&tmp);
+            let king = find_king(&board, side);
+            !attacked(&board, king, side ^ 1)
+        })
+        .collect()
+}
+
+/// Counts the leaf nodes `depth` plies from `pos` using only [`legal_moves`].
+#[must_use]
+pub fn perft(pos: &Position, depth: u8) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+
+    legal_moves(pos)
+        .into_iter()
+        .map(|mov| {
+            let mut tmp = *pos;
+            tmp.make(mov);
+            perft(&tmp, depth - 1)
+        })
+        .sum()
+}
+
+fn pseudo_legal(pos: &Position) -> Vec<Move> {
+    let board = board(pos);
+    let side = pos.stm();
+    let enp = en_passant(pos, &board);
+    let mut moves = Vec::new();
+
+    for from in 0..64 {
+        match board[from] {
+            Some((owner, Piece::PAWN)) if owner == side => {
+                pawn(side, &board, enp, from, &mut moves);
+            }
+            Some((owner, piece)) if owner == side => {
+                let (steps, slides): (&[(i8, i8)], bool) = match piece {
+                    Piece::KNIGHT => (&KNIGHT, false),
+                    Piece::BISHOP => (&BISHOP, true),
+                    Piece::ROOK => (&ROOK, true),
+                    Piece::QUEEN => (&KING, true),
+                    _ => (&KING, false),
+                };
+                for &step in steps {
+                    let mut to = from;
+                    while let Some(next) = offset(to, step) {
+                        to = next;
+                        match board[to] {
+                            None => moves.push(new(from, to, Flag::QUIET, piece)),
+                            Some((owner, _)) => {
+                                if owner != side {
+                                    moves.push(new(from, to, Flag::CAP, piece));
+                                }
+                                break;
+                            }
+                        }
+                        if !slides {
+                            break;
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    castles(pos, &board, &mut moves);
+    moves
+}
+
+fn pawn(side: usize, board: &Board, enp: Option<usize>, from: usize, moves: &mut Vec<Move>) {
+    let (dir, start, last) = if side == Side::WHITE {
+        (1, 1, 7)
+    } else {
+        (-1, 6, 0)
+    };
+
+    let mut push = |to: usize, flag: u8| {
+        if to / 8 == last {
+            for promo in PROMOTIONS {
+                moves.push(new(from, to, promo | flag, Piece::PAWN));
+            }
+        } else {
+            moves.push(new(from, to, flag, Piece::PAWN));
+        }
+    };
+
+    if let Some(to) = offset(from, (0, dir)).filter(|&to| board[to].is_none()) {
+        push(to, Flag::QUIET);
+        if from / 8 == start {
+            if let Some(two) = offset(to, (0, dir)).filter(|&two| board[two].is_none()) {
+                push(two, Flag::DBL);
+            }
+        }
+    }
+
+    for file in [-1, 1] {
+        let Some(to) = offset(from, (file, dir)) else {
+            continue;
+        };
+        match board[to] {
+            Some((owner, _)) if owner != side => push(to, Flag::CAP),
+            None if enp == Some(to) => push(to, Flag::ENP),
+            _ => {}
+        }
+    }
+}
+
+// the en passant target as written in the FEN, if the pawn that pushed past
+// it is on the square in front, so a capture is left to the legality check
+// in legal_moves rather than to Position::enp_sq
+fn en_passant(pos: &Position, board: &Board) -> Option<usize> {
+    let fen = pos.to_fen();
+    let &[file @ b'a'..=b'h', rank] = fen.split(' ').nth(3)?.as_bytes() else {
+        return None;
+    };
+    let side = pos.stm();
+    let (target_rank, dir) = if side == Side::WHITE {
+        (b'6', -1)
+    } else {
+        (b'3', 1)
+    };
+    if rank != target_rank {
+        return None;
+    }
+
+    let target = 8 * usize::from(rank - b'1') + usize::from(file - b'a');
+    let pushed = offset(target, (0, dir))?;
+    let start = offset(target, (0, -dir))?;
+    (board[target].is_none()
+        && board[start].is_none()
+        && board[pushed] == Some((side ^ 1, Piece::PAWN)))
+    .then_some(target)
+}
+
+// the king and rook must have a clear path to their squares, and the
+// king may not start in, pass through or land on an attacked square
+fn castles(pos: &Position, board: &Board, moves: &mut Vec<Move>) {
+    let side = pos.stm();
+    let king = find_king(board, side);
+    let rank = king / 8 * 8;
+
+    for (ks, flag) in [(0, Flag::QS), (1, Flag::KS)] {
+        if pos.rights() & Right::TABLE[side][ks] == 0 {
+            continue;
+        }
+
+        let rook = usize::from(pos.castling_rook(side, ks));
+        let (king_to, rook_to) = if ks == 1 {
+            (rank + 6, rank + 5)
+        } else {
+            (rank + 2, rank + 3)
+        };
+
+        let clear = between(king, king_to)
+            .chain(between(rook, rook_to))
+            .all(|sq| sq == king || sq == rook || board[sq].is_none());
+
+        let mut without_rook = *board;
+        without_rook[rook] = None;
+        let safe = between(king, king_to).all(|sq| !attacked(&without_rook, sq, side ^ 1));
+
+        if clear && safe {
+            moves.push(new(king, rook, flag, Piece::KING));
+        }
+    }
+}
+
+// squares from `a` to `b` on a rank, both included
+fn between(a: usize, b: usize) -> impl Iterator<Item = usize> {
+    a.min(b)..=a.max(b)
+}
+
+// whether any piece of `by` attacks `sq`, looking outwards from it
+fn attacked(board: &Board, sq: usize, by: usize) -> bool {
+    let pawn_rank = if by == Side::WHITE { -1 } else { 1 };
+    let hits = |steps: &[(i8, i8)], slides: bool, pieces: &[usize]| {
+        steps.iter().any(|&step| {
+            let mut to = sq;
+            while let Some(next) = offset(to, step) {
+                to = next;
+                if let Some((owner, piece)) = board[to] {
+                    return owner == by && pieces.contains(&piece);
+                }
+                if !slides {
+                    break;
+                }
+            }
+            false
+        })
+    };
+
+    hits(&[(-1, pawn_rank), (1, pawn_rank)], false, &[Piece::PAWN])
+        || hits(&KNIGHT, false, &[Piece::KNIGHT])
+        || hits(&KING, false, &[Piece::KING])
+        || hits(&ROOK, true, &[Piece::ROOK, Piece::QUEEN])
+        || hits(&BISHOP, true, &[Piece::BISHOP, Piece::QUEEN])
+}
+
+fn board(pos: &Position) -> Board {
+    let mut board = [None; 64];
+    for side in [Side::WHITE, Side::BLACK] {
+        for piece in Piece::PAWN..=Piece::KING {
+            for (sq, square) in board.iter_mut().enumerate() {
+                if pos.piece(side) & pos.piece(piece) & (1 << sq) > 0 {
+                    *square = Some((side, piece));
+                }
+            }
+        }
+    }
+    board
+}
+
+fn find_king(board: &Board, side: usize) -> usize {
+    board
+        .iter()
+        .position(|&square| square == Some((side, Piece::KING)))
+        .unwrap_or(64)
+}
+
+fn offset(sq: usize, (file, rank): (i8, i8)) -> Option<usize> {
+    let file = (sq % 8).checked_add_signed(file.into())?;
+    let rank = (sq / 8).checked_add_signed(rank.into())?;
+    (file < 8 && rank < 8).then_some(8 * rank + file)
+}
+
+fn new(from: usize, to: usize, flag: u8, piece: usize) -> Move {
+    Move::new(from as u8, to as u8, flag, piece as u8)
+}
@@ -0,0 +1,164 @@
+use super::{
+    consts::{Flag, Piece},
+    position::{Move, Position},
+};
+
+const PIECES: [u8; 8] = *b"  PNBRQK";
+
+impl Position {
+    /// The legal move `mov` in Standard Algebraic Notation, such as `Nbd2`, `exd6`,
+    /// `e8=Q+` or `O-O-O#`, moves are disambiguated by file, then rank, then both.
+    #[must_use]
+    pub fn to_san(&self, mov: Move) -> String {
+        let mut san = String::new();
+        let piece = mov.moved();
+        let capture = mov.flag() & Flag::CAP > 0;
+
+        if mov.is_castle() {
+            san.push_str(if mov.flag() == Flag::KS {
+                "O-O"
+            } else {
+                "O-O-O"
+            });
+        } else {
+            if piece == Piece::PAWN {
+                if capture {
+                    san.push(file_char(mov.from()));
+                }
+            } else {
+                san.push(PIECES[piece] as char);
+                san.push_str(&self.disambiguation(mov));
+            }
+
+            if capture {
+                san.push('x');
+            }
+            san.push(file_char(mov.to()));
+            san.push(rank_char(mov.to()));
+
+            if mov.flag() >= Flag::NPR {
+                san.push('=');
+                san.push(PIECES[usize::from(mov.flag() & 3) + Piece::KNIGHT] as char);
+            }
+        }
+
+        let mut tmp = *self;
+        tmp.make(mov);
+        if tmp.checkers() > 0 {
+            san.push(if tmp.gen().len == 0 { '#' } else { '+' });
+        }
+
+        san
+    }
+
+    // from square details needed to tell `mov` apart from
+    // other moves of the same kind of piece to the same square
+    fn disambiguation(&self, mov: Move) -> String {
+        let moves = self.gen();
+        let others: Vec<Move> = moves.list[..moves.len]
+            .iter()
+            .copied()
+            .filter(|other| {
+                other.moved() == mov.moved()
+                    && other.to() == mov.to()
+                    && other.from() != mov.from()
+                    && !other.is_castle()
+            })
+            .collect();
+
+        if others.is_empty() {
+            String::new()
+        } else if others
+            .iter()
+            .all(|other| other.from() & 7 != mov.from() & 7)
+        {
+            file_char(mov.from()).to_string()
+        } else if others
+            .iter()
+            .all(|other| other.from() / 8 != mov.from() / 8)
+        {
+            rank_char(mov.from()).to_string()
+        } else {
+            format!("{}{}", file_char(mov.from()), rank_char(mov.from()))
+        }
+    }
+
+    /// Finds the legal move written in Standard Algebraic Notation, ignoring
+    /// check marks and annotations, or `None` if there is no such move or
+    /// more than one. Castling may also be written with zeros.
+    #[must_use]
+    pub fn parse_san(&self, san: &str) -> Option<Move> {
+        let san = san.trim_end_matches(['+', '#', '!', '?']);
+        let moves = self.gen();
+        let list = &moves.list[..moves.len];
+
+        let castle = match san {
+            "O-O" | "0-0" => Some(Flag::KS),
+            "O-O-O" | "0-0-0" => Some(Flag::QS),
+            _ => None,
+        };
+        if let Some(flag) = castle {
+            return list.iter().copied().find(|mov| mov.flag() == flag);
+        }
+
+        let mut chars = san.as_bytes();
+
+        let piece = match chars.first()? {
+            &pc @ (b'N' | b'B' | b'R' | b'Q' | b'K') => {
+                chars = &chars[1..];
+                PIECES.iter().position(|&ch| ch == pc)?
+            }
+            _ => Piece::PAWN,
+        };
+
+        let mut promo = None;
+        if let [rest @ .., pc @ (b'N' | b'B' | b'R' | b'Q')] = chars {
+            promo = PIECES.iter().position(|ch| ch == pc);
+            chars = rest.strip_suffix(b"=").unwrap_or(rest);
+        }
+
+        let [hint @ .., file @ b'a'..=b'h', rank @ b'1'..=b'8'] = chars else {
+            return None;
+        };
+        let to = 8 * (rank - b'1') + file - b'a';
+
+        let mut from_file = None;
+        let mut from_rank = None;
+        for &ch in hint.iter().filter(|&&ch| ch != b'x') {
+            match ch {
+                b'a'..=b'h' if from_file.is_none() => from_file = Some(ch - b'a'),
+                b'1'..=b'8' if from_rank.is_none() => from_rank = Some(ch - b'1'),
+                _ => return None,
+            }
+        }
+
+        // pawns without a file hint move straight ahead, captures
+        // must name the file they're made from
+        if piece == Piece::PAWN {
+            from_file = from_file.or(Some(file - b'a'));
+        }
+
+        let mut found = list.iter().copied().filter(|mov| {
+            let mov_promo =
+                (mov.flag() >= Flag::NPR).then(|| usize::from(mov.flag() & 3) + Piece::KNIGHT);
+
+            mov.moved() == piece
+                && mov.to() == to
+                && !mov.is_castle()
+                && mov_promo == promo
+                && from_file.is_none_or(|file| mov.from() & 7 == file)
+                && from_rank.is_none_or(|rank| mov.from() / 8 == rank)
+        });
+
+        let mov = found.next()?;
+        found.next().is_none().then_some(mov)
+    }
+}
+
+fn file_char(sq: u8) -> char {
+    (b'a' + (sq & 7)) as char
+}
+
+fn rank_char(sq: u8) -> char {
+    (b'1' + sq / 8) as char
+}
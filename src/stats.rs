@@ -0,0 +1,92 @@
+use super::{consts::Flag, position::Position};
+use std::ops::AddAssign;
+
+/// Counts of the kinds of moves leading to the leaves of a perft tree,
+/// matching the tables on the Chess Programming Wiki.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct PerftStats {
+    /// Leaf nodes.
+    pub nodes: u64,
+    /// Captures, including en passant.
+    pub captures: u64,
+    /// En passant captures.
+    pub en_passants: u64,
+    /// Castling moves.
+    pub castles: u64,
+    /// Promotions, including captures.
+    pub promotions: u64,
+    /// Moves giving check.
+    pub checks: u64,
+    /// Checks not given by the moved piece.
+    pub discovered_checks: u64,
+    /// Checks by two pieces.
+    pub double_checks: u64,
+    /// Checkmates.
+    pub checkmates: u64,
+}
+
+impl AddAssign for PerftStats {
+    fn add_assign(&mut self, rhs: Self) {
+        self.nodes += rhs.nodes;
+        self.captures += rhs.captures;
+        self.en_passants += rhs.en_passants;
+        self.castles += rhs.castles;
+        self.promotions += rhs.promotions;
+        self.checks += rhs.checks;
+        self.discovered_checks += rhs.discovered_checks;
+        self.double_checks += rhs.double_checks;
+        self.checkmates += rhs.checkmates;
+    }
+}
+
+/// Walks the legal move tree of `pos` to `depth`, classifying the moves into the leaves.
+///
+/// Depth 0 counts `pos` itself as the only node, with no moves to classify.
+#[must_use]
+pub fn perft_stats(pos: &Position, depth: u8) -> PerftStats {
+    let mut stats = PerftStats::default();
+    if depth == 0 {
+        stats.nodes = 1;
+        return stats;
+    }
+
+    let moves = pos.gen();
+
+    for &mov in &moves.list[..moves.len] {
+        let mut tmp = *pos;
+        tmp.make(mov);
+
+        if depth > 1 {
+            stats += perft_stats(&tmp, depth - 1);
+            continue;
+        }
+
+        let flag = mov.flag();
+        stats.nodes += 1;
+        stats.captures += u64::from(flag & Flag::CAP > 0);
+        stats.en_passants += u64::from(flag == Flag::ENP);
+        stats.castles += u64::from(mov.is_castle());
+        stats.promotions += u64::from(flag >= Flag::NPR);
+
+        let checkers = tmp.checkers();
+        if checkers == 0 {
+            continue;
+        }
+
+        // squares the moving side's pieces landed on, a check is
+        // only counted as discovered if none of them give it
+        let landed = if mov.is_castle() {
+            let (king_to, rook_to) = mov.castle_squares();
+            (1 << king_to) | (1 << rook_to)
+        } else {
+            1 << mov.to()
+        };
+
+        stats.checks += 1;
+        stats.discovered_checks += u64::from(checkers & landed == 0);
+        stats.double_checks += u64::from(checkers.count_ones() > 1);
+        stats.checkmates += u64::from(tmp.gen().len == 0);
+    }
+
+    stats
+}
@@ -0,0 +1,59 @@
+use super::{consts::Piece, position::Position};
+
+/// Whether the game is over, and if so how.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Status {
+    /// The side to move has a legal move and the game is not drawn.
+    Ongoing,
+    /// The side to move is in check with no legal moves, and has lost.
+    Checkmate,
+    /// The side to move is not in check but has no legal moves.
+    Stalemate,
+    /// Neither side has enough pieces left to checkmate.
+    InsufficientMaterial,
+    /// 100 plies have been played without a capture or pawn move.
+    FiftyMoveRule,
+}
+
+// a1, c1, ..., h8
+const DARK_SQUARES: u64 = 0x55AA_55AA_55AA_55AA;
+
+impl Position {
+    /// Result of the game in this position, a checkmate
+    /// on the hundredth ply still ends the game as a loss.
+    #[must_use]
+    pub fn status(&self) -> Status {
+        if self.gen().len == 0 {
+            return if self.checkers() > 0 {
+                Status::Checkmate
+            } else {
+                Status::Stalemate
+            };
+        }
+
+        if self.is_insufficient_material() {
+            Status::InsufficientMaterial
+        } else if self.halfmove_clock() >= 100 {
+            Status::FiftyMoveRule
+        } else {
+            Status::Ongoing
+        }
+    }
+
+    /// Whether only kings remain, along with a single minor piece
+    /// or any number of bishops all on the same colour squares.
+    #[must_use]
+    pub fn is_insufficient_material(&self) -> bool {
+        let heavy = self.piece(Piece::PAWN) | self.piece(Piece::ROOK) | self.piece(Piece::QUEEN);
+        if heavy > 0 {
+            return false;
+        }
+
+        let knights = self.piece(Piece::KNIGHT);
+        let bishops = self.piece(Piece::BISHOP);
+        let minors = knights | bishops;
+
+        minors.count_ones() <= 1
+            || knights == 0 && (bishops & DARK_SQUARES == 0 || bishops & !DARK_SQUARES == 0)
+    }
+}
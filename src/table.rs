@@ -0,0 +1,75 @@
+#[derive(Clone, Copy, Default)]
+struct Entry {
+    key: u64,
+    count: u64,
+    depth: u8,
+}
+
+// the first entry is only replaced by a search of equal or greater depth,
+// the second entry is always replaced
+#[derive(Clone, Copy, Default)]
+struct Bucket {
+    deep: Entry,
+    recent: Entry,
+}
+
+/// Fixed size table of perft counts keyed by position hash and depth.
+pub struct HashTable {
+    buckets: Vec<Bucket>,
+}
+
+impl HashTable {
+    /// Allocates a table of roughly the given size.
+    /// # Errors
+    /// Returns [`TableTooLarge`] if that much memory can't be allocated.
+    pub fn new(megabytes: usize) -> Result<Self, TableTooLarge> {
+        let too_large = || TableTooLarge(megabytes);
+        let bytes = megabytes.checked_mul(1024 * 1024).ok_or_else(too_large)?;
+        let len = (bytes / std::mem::size_of::<Bucket>()).max(1);
+
+        let mut buckets = Vec::new();
+        buckets.try_reserve_exact(len).map_err(|_| too_large())?;
+        buckets.resize(len, Bucket::default());
+        Ok(Self { buckets })
+    }
+
+    fn index(&self, key: u64) -> usize {
+        ((u128::from(key) * self.buckets.len() as u128) >> 64) as usize
+    }
+
+    /// Looks up the count stored for `key` at `depth`.
+    #[must_use]
+    pub fn get(&self, key: u64, depth: u8) -> Option<u64> {
+        let bucket = &self.buckets[self.index(key)];
+
+        [bucket.deep, bucket.recent]
+            .into_iter()
+            .find(|entry| entry.key == key && entry.depth == depth)
+            .map(|entry| entry.count)
+    }
+
+    /// Stores a count, possibly replacing another entry in the same bucket.
+    pub fn insert(&mut self, key: u64, depth: u8, count: u64) {
+        let idx = self.index(key);
+        let bucket = &mut self.buckets[idx];
+        let entry = Entry { key, count, depth };
+
+        if depth >= bucket.deep.depth {
+            bucket.deep = entry;
+        } else {
+            bucket.recent = entry;
+        }
+    }
+}
+
+/// A hash table size, in megabytes, that could not be allocated.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TableTooLarge(pub usize);
+
+impl std::fmt::Display for TableTooLarge {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "could not allocate a hash table of {} MB", self.0)
+    }
+}
+
+impl std::error::Error for TableTooLarge {}
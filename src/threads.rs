@@ -0,0 +1,104 @@
+use super::{
+    perft,
+    position::{Move, Position},
+};
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+};
+
+// subtrees are split until there are this many per thread,
+// so that threads finishing early can pick up more work
+const ITEMS_PER_THREAD: usize = 8;
+
+struct Item {
+    root: usize,
+    pos: Position,
+    depth: u8,
+}
+
+/// Counts each root move's subtree to `depth`, splitting the work across `threads`.
+///
+/// The counts are in the order of [`Position::gen`], and match [`perft`].
+/// # Panics
+/// Panics if `depth` is 0, as there are no root moves to count, or if a worker thread panics.
+#[must_use]
+pub fn perft_parallel<const BULK: bool>(
+    pos: &Position,
+    depth: u8,
+    threads: usize,
+) -> Vec<(Move, u64)> {
+    assert!(depth > 0, "perft_parallel needs a depth of at least 1");
+    let moves = pos.gen();
+    let mut items = Vec::with_capacity(moves.len);
+
+    for m_idx in 0..moves.len {
+        let mut tmp = *pos;
+        tmp.make(moves.list[m_idx]);
+        items.push(Item {
+            root: m_idx,
+            pos: tmp,
+            depth: depth - 1,
+        });
+    }
+
+    // split the subtrees one ply deeper until there is enough work,
+    // stopping if every subtree ends in mate or stalemate
+    while !items.is_empty()
+        && items.len() < threads * ITEMS_PER_THREAD
+        && items.iter().all(|item| item.depth > 1)
+    {
+        let mut split = Vec::new();
+        for item in &items {
+            let moves = item.pos.gen();
+            for m_idx in 0..moves.len {
+                let mut tmp = item.pos;
+                tmp.make(moves.list[m_idx]);
+                split.push(Item {
+                    root: item.root,
+                    pos: tmp,
+                    depth: item.depth - 1,
+                });
+            }
+        }
+        items = split;
+    }
+
+    let next = AtomicUsize::new(0);
+    let mut counts = vec![0; moves.len];
+
+    thread::scope(|s| {
+        let handles: Vec<_> = (0..threads)
+            .map(|_| {
+                s.spawn(|| {
+                    let mut done = Vec::new();
+                    loop {
+                        let idx = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(item) = items.get(idx) else {
+                            break done;
+                        };
+
+                        let num = if item.depth == 0 {
+                            1
+                        } else {
+                            perft::<false, BULK>(&item.pos, item.depth)
+                        };
+                        done.push((item.root, num));
+                    }
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            for (root, num) in handle.join().unwrap() {
+                counts[root] += num;
+            }
+        }
+    });
+
+    moves.list[..moves.len]
+        .iter()
+        .copied()
+        .zip(counts)
+        .collect()
+}
@@ -0,0 +1,74 @@
+use super::{cli::Options, run};
+use perft::{HashTable, Position, STARTPOS};
+use std::io::{self, BufRead};
+
+pub fn uci_loop(opts: &Options, mut table: Option<&mut HashTable>) -> io::Result<()> {
+    let mut pos = Position::parse_fen(STARTPOS);
+    let mut chess960 = opts.chess960;
+
+    for line in io::stdin().lock().lines() {
+        let line = line?;
+        let mut words = line.split_whitespace();
+
+        match words.next() {
+            Some("uci") => {
+                println!("id name perft");
+                println!("id author Jamie Whiting");
+                println!("option name UCI_Chess960 type check default {chess960}");
+                println!("uciok");
+            }
+            Some("isready") => println!("readyok"),
+            Some("ucinewgame") => pos = Position::parse_fen(STARTPOS),
+            Some("setoption") => match parse_option(words) {
+                Some(("UCI_Chess960", value)) => chess960 = value == "true",
+                Some((name, _)) => println!("info string unknown option '{name}'"),
+                None => println!("info string expected 'setoption name <id> value <x>'"),
+            },
+            Some("position") => match parse_position(words) {
+                Ok(new) => {
+                    pos = new;
+                    pos.set_chess960(chess960 || pos.is_chess960());
+                }
+                Err(err) => println!("info string {err}"),
+            },
+            Some("go") => match (words.next(), words.next().map(str::parse)) {
+                (Some("perft"), Some(Ok(depth))) if depth > 0 => {
+                    let total = run(&pos, depth, true, opts, table.as_deref_mut());
+                    println!();
+                    println!("Nodes searched: {total}");
+                    println!();
+                }
+                _ => println!("info string only 'go perft <depth>' is supported"),
+            },
+            Some("quit") => break,
+            Some(cmd) => println!("info string unknown command '{cmd}'"),
+            None => {}
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_option<'a, I: Iterator<Item = &'a str>>(mut words: I) -> Option<(&'a str, &'a str)> {
+    match (words.next(), words.next(), words.next(), words.next()) {
+        (Some("name"), Some(name), Some("value"), Some(value)) => Some((name, value)),
+        _ => None,
+    }
+}
+
+fn parse_position<'a, I: Iterator<Item = &'a str>>(mut words: I) -> Result<Position, String> {
+    let mut pos = match words.next() {
+        Some("startpos") => Position::parse_fen(STARTPOS),
+        Some("fen") => {
+            let fen: Vec<&str> = words.by_ref().take_while(|&word| word != "moves").collect();
+            Position::from_fen(&fen.join(" ")).map_err(|err| format!("invalid fen: {err}"))?
+        }
+        _ => return Err("expected 'startpos' or 'fen'".into()),
+    };
+
+    let moves: Vec<&str> = words.collect();
+    pos.play_uci_moves(&moves.join(" "))
+        .map_err(|err| err.to_string())?;
+
+    Ok(pos)
+}
@@ -0,0 +1,64 @@
+#![cfg(any(feature = "pext", feature = "magic"))]
+
+#[cfg(feature = "magic")]
+use perft::Magic;
+#[cfg(feature = "pext")]
+use perft::Pext;
+use perft::{Classic, Sliders};
+
+// every subset of the squares that can block each slider, along with
+// a few squares outside that should never change the attacks
+fn agree(mask: impl Fn(usize) -> u64, attacks: [fn(usize, u64) -> u64; 2]) {
+    for sq in 0..64 {
+        let mask = mask(sq);
+        let mut occ = 0u64;
+        loop {
+            for extra in [0, !mask, 0xFF00_0000_0000_00FF & !mask] {
+                let occ = occ | extra;
+                assert_eq!(attacks[0](sq, occ), attacks[1](sq, occ), "{sq} {occ:#x}");
+            }
+            occ = occ.wrapping_sub(mask) & mask;
+            if occ == 0 {
+                break;
+            }
+        }
+    }
+}
+
+// attacks on an empty board without the last square of each ray
+fn relevant(attacks: fn(usize, u64) -> u64) -> impl Fn(usize) -> u64 {
+    move |sq| {
+        let empty = attacks(sq, 0);
+        let mut mask = 0;
+        for bit in (0..64).filter(|bit| empty & (1 << bit) > 0) {
+            if attacks(sq, 1 << bit) != attacks(sq, 0) {
+                mask |= 1 << bit;
+            }
+        }
+        mask
+    }
+}
+
+#[cfg(feature = "pext")]
+#[test]
+fn pext_rook_matches_classic() {
+    agree(relevant(Classic::rook), [Pext::rook, Classic::rook]);
+}
+
+#[cfg(feature = "pext")]
+#[test]
+fn pext_bishop_matches_classic() {
+    agree(relevant(Classic::bishop), [Pext::bishop, Classic::bishop]);
+}
+
+#[cfg(feature = "magic")]
+#[test]
+fn magic_rook_matches_classic() {
+    agree(relevant(Classic::rook), [Magic::rook, Classic::rook]);
+}
+
+#[cfg(feature = "magic")]
+#[test]
+fn magic_bishop_matches_classic() {
+    agree(relevant(Classic::bishop), [Magic::bishop, Classic::bishop]);
+}
@@ -0,0 +1,66 @@
+use perft::{
+    bisect::{bisect, Reference, UciEngine},
+    perft_parallel, Position,
+};
+use std::{env, fs, process};
+
+const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+const PERFT: &str = env!("CARGO_BIN_EXE_perft");
+
+#[test]
+fn engine_divide_matches() {
+    let mut engine = UciEngine::spawn(&format!("{PERFT} --uci")).unwrap();
+    let pos = Position::parse_fen(KIWIPETE);
+
+    let theirs = engine.divide(KIWIPETE, &["e1g1".into()], 2).unwrap();
+    let mut castled = pos;
+    castled.play_uci_moves("e1g1").unwrap();
+    let ours = perft_parallel::<true>(&castled, 2, 1);
+
+    assert_eq!(theirs.len(), ours.len());
+    for ((mov, count), (uci, num)) in ours.iter().zip(&theirs) {
+        assert_eq!((mov.to_uci(false), *count), (uci.clone(), *num));
+    }
+
+    assert!(bisect(&mut engine, &pos, 3, 1).unwrap().is_none());
+}
+
+#[test]
+fn no_legal_moves_agree() {
+    let mut engine = UciEngine::spawn(&format!("{PERFT} --uci")).unwrap();
+    for fen in [
+        "7k/6Q1/6K1/8/8/8/8/8 b - - 0 1",
+        "7k/8/6QK/8/8/8/8/8 b - - 0 1",
+    ] {
+        let pos = Position::parse_fen(fen);
+        assert!(engine.divide(fen, &[], 2).unwrap().is_empty());
+        assert!(bisect(&mut engine, &pos, 2, 2).unwrap().is_none(), "{fen}");
+    }
+}
+
+#[test]
+fn finds_missing_castle_through_engine() {
+    let log = env::temp_dir().join(format!("perft-bisect-{}.log", process::id()));
+    let script = "tests/engines/no_black_kingside.sh";
+    let cmd = format!("sh {script} {PERFT} {}", log.display());
+
+    let div = {
+        let mut engine = UciEngine::spawn(&cmd).unwrap();
+        bisect(&mut engine, &Position::parse_fen(KIWIPETE), 3, 1)
+            .unwrap()
+            .unwrap()
+    };
+
+    assert_eq!(div.line.len(), 1);
+    assert!(div.line[0].ours > div.line[0].theirs);
+    assert_eq!(div.missing, ["e8g8"]);
+    assert!(div.extra.is_empty());
+
+    // the handshake comes first, and dropping the engine sends quit
+    let sent = fs::read_to_string(&log).unwrap();
+    fs::remove_file(&log).unwrap();
+    let sent = sent.lines().collect::<Vec<_>>();
+    assert_eq!(sent.first(), Some(&"uci"));
+    assert_eq!(sent.last(), Some(&"quit"));
+    assert!(sent.contains(&"go perft 3"));
+}
@@ -0,0 +1,12 @@
+#!/bin/sh
+# Stand-in UCI engine for tests/bisect.rs: forwards commands to the perft
+# binary given as $1, dropping black's kingside castling right from
+# 'position' commands, and appends every command it gets to the file $2.
+perft=$1
+log=$2
+
+while read -r line; do
+    printf '%s\n' "$line" >> "$log"
+    printf '%s\n' "$line" | sed 's/ KQkq / KQq /'
+    [ "$line" = quit ] && break
+done | "$perft" --uci
@@ -0,0 +1,55 @@
+use perft::{Game, Position, STARTPOS};
+
+const SHUFFLE: &str = "g1f3 g8f6 f3g1 f6g8";
+
+#[test]
+fn threefold_repetition() {
+    let mut game = Game::new(Position::parse_fen(STARTPOS));
+    assert!(game.is_repetition(1));
+    assert!(!game.is_repetition(2));
+
+    game.play_uci_moves(SHUFFLE).unwrap();
+    assert!(game.is_repetition(2));
+    assert!(!game.is_repetition(3));
+
+    game.play_uci_moves(SHUFFLE).unwrap();
+    assert!(game.is_repetition(3));
+
+    // back to the second time this position was reached
+    game.unmake();
+    assert!(game.is_repetition(2));
+    assert!(!game.is_repetition(3));
+    assert_eq!(game.ply(), 7);
+}
+
+#[test]
+fn unusable_en_passant_square_repeats() {
+    // no black pawn can take on e3, so the position after e2e4 is
+    // the same as after each of the knight shuffles that follow
+    let mut game = Game::new(Position::parse_fen(STARTPOS));
+    game.play_uci_moves("e2e4 g8f6 g1f3 f6g8 f3g1 g8f6 g1f3 f6g8 f3g1")
+        .unwrap();
+    assert!(game.is_repetition(3));
+}
+
+#[test]
+fn irreversible_moves_end_history() {
+    // the pawn move means the start position can no longer repeat
+    let mut game = Game::new(Position::parse_fen(STARTPOS));
+    game.play_uci_moves("e2e4 e7e5 e1e2 e8e7 e2e1 e7e8")
+        .unwrap();
+    assert!(!game.is_repetition(2));
+
+    // losing castling rights is irreversible too, even though
+    // the pieces are back where they started
+    let fen = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1";
+    let mut game = Game::new(Position::parse_fen(fen));
+    game.play_uci_moves("a1b1 a8b8 b1a1 b8a8").unwrap();
+    assert!(!game.is_repetition(2));
+    game.play_uci_moves("a1b1 a8b8 b1a1 b8a8").unwrap();
+    assert!(game.is_repetition(2));
+    assert_eq!(
+        game.position().to_fen(),
+        "r3k2r/8/8/8/8/8/8/R3K2R w Kk - 8 5"
+    );
+}
@@ -0,0 +1,162 @@
+use perft::{
+    perft, perft_hashed, perft_parallel, perft_stats, perft_unmake, perft_with_progress, HashTable,
+    PerftStats, Position, TableTooLarge,
+};
+
+const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+
+// (fen, counts from depth 1) for each line of the suite, only
+// keeping the depths that are quick enough for a debug build
+fn suite(epd: &str) -> Vec<(Position, Vec<u64>)> {
+    epd.lines()
+        .map(|line| {
+            let mut fields = line.split(';');
+            let pos = Position::parse_fen(fields.next().unwrap());
+            let counts = fields
+                .map(|depth| depth.split_whitespace().nth(1).unwrap().parse().unwrap())
+                .take_while(|&count| count < 100_000)
+                .collect();
+            (pos, counts)
+        })
+        .collect()
+}
+
+#[test]
+fn suite_counts() {
+    for (pos, counts) in suite(include_str!("../perft_results.txt")) {
+        for (d, &count) in counts.iter().enumerate() {
+            assert_eq!(perft::<false, true>(&pos, d as u8 + 1), count, "{pos}");
+            assert_eq!(perft::<false, false>(&pos, d as u8 + 1), count, "{pos}");
+        }
+    }
+}
+
+#[test]
+fn chess960_suite_counts() {
+    for (pos, counts) in suite(include_str!("../perft_960.txt")) {
+        for (d, &count) in counts.iter().enumerate() {
+            assert_eq!(perft::<false, false>(&pos, d as u8 + 1), count, "{pos}");
+        }
+    }
+}
+
+#[test]
+fn unmake_matches_copy_make() {
+    let suites = [
+        include_str!("../perft_results.txt"),
+        include_str!("../perft_960.txt"),
+    ];
+    for (pos, counts) in suites.into_iter().flat_map(suite) {
+        let mut unmade = pos;
+        for (d, &count) in counts.iter().enumerate() {
+            assert_eq!(
+                perft_unmake::<false, false>(&mut unmade, d as u8 + 1),
+                count,
+                "{pos}"
+            );
+            assert_eq!(unmade.to_fen(), pos.to_fen());
+            assert_eq!(unmade.hash(), pos.hash());
+        }
+    }
+}
+
+#[test]
+fn hashed_matches_suite() {
+    let mut table = HashTable::new(1).unwrap();
+    for (pos, counts) in suite(include_str!("../perft_results.txt")) {
+        for (d, &count) in counts.iter().enumerate() {
+            assert_eq!(
+                perft_hashed::<false, true>(&pos, d as u8 + 1, &mut table),
+                count
+            );
+        }
+    }
+}
+
+#[test]
+fn depth_zero_counts_the_position() {
+    let mut pos = Position::parse_fen(KIWIPETE);
+    let mut table = HashTable::new(1).unwrap();
+    assert_eq!(perft::<true, true>(&pos, 0), 1);
+    assert_eq!(perft::<false, false>(&pos, 0), 1);
+    assert_eq!(perft_unmake::<false, true>(&mut pos, 0), 1);
+    assert_eq!(perft_hashed::<false, true>(&pos, 0, &mut table), 1);
+    assert_eq!(perft_stats(&pos, 0).nodes, 1);
+}
+
+#[test]
+#[should_panic(expected = "depth of at least 1")]
+fn parallel_needs_a_root_move_depth() {
+    let _ = perft_parallel::<true>(&Position::parse_fen(KIWIPETE), 0, 2);
+}
+
+#[test]
+fn oversized_table_is_an_error() {
+    for megabytes in [usize::MAX, usize::MAX / (1024 * 1024)] {
+        let err = HashTable::new(megabytes).err();
+        assert_eq!(err, Some(TableTooLarge(megabytes)));
+    }
+}
+
+#[test]
+fn parallel_matches_divide() {
+    let pos = Position::parse_fen(KIWIPETE);
+    let counts = perft_parallel::<true>(&pos, 3, 3);
+    let moves = pos.gen();
+
+    assert_eq!(counts.len(), moves.len);
+    for ((mov, count), expected) in counts.iter().zip(&moves.list[..moves.len]) {
+        let mut tmp = pos;
+        tmp.make(*expected);
+        assert_eq!(mov.to_uci(false), expected.to_uci(false));
+        assert_eq!(*count, perft::<false, true>(&tmp, 2));
+    }
+
+    assert_eq!(counts.iter().map(|(_, count)| count).sum::<u64>(), 97862);
+}
+
+#[test]
+fn parallel_without_legal_moves() {
+    let mated = Position::parse_fen("7k/6Q1/6K1/8/8/8/8/8 b - - 0 1");
+    let stalemated = Position::parse_fen("7k/8/6QK/8/8/8/8/8 b - - 0 1");
+    for pos in [mated, stalemated] {
+        for depth in 1..=4 {
+            assert!(perft_parallel::<true>(&pos, depth, 2).is_empty(), "{pos}");
+            assert!(perft_parallel::<false>(&pos, depth, 4).is_empty(), "{pos}");
+        }
+    }
+}
+
+#[test]
+fn progress_after_each_root_move() {
+    let pos = Position::parse_fen(KIWIPETE);
+    let mut reports = Vec::new();
+    let total = perft_with_progress::<false, _, _>(&pos, 3, perft::<false, true>, |progress| {
+        reports.push(*progress)
+    });
+
+    assert_eq!(total, 97862);
+    assert_eq!(reports.len(), 48);
+    for (idx, progress) in reports.iter().enumerate() {
+        assert_eq!((progress.done, progress.total), (idx + 1, 48));
+    }
+    assert_eq!(reports[47].nodes, total);
+    assert!(reports.windows(2).all(|pair| pair[0].nodes < pair[1].nodes));
+}
+
+#[test]
+fn kiwipete_stats() {
+    let stats = perft_stats(&Position::parse_fen(KIWIPETE), 3);
+    let expected = PerftStats {
+        nodes: 97862,
+        captures: 17102,
+        en_passants: 45,
+        castles: 3162,
+        promotions: 0,
+        checks: 993,
+        discovered_checks: 0,
+        double_checks: 0,
+        checkmates: 1,
+    };
+    assert_eq!(stats, expected);
+}
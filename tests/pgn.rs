@@ -0,0 +1,74 @@
+use perft::{
+    pgn::{PgnErrorKind, PgnReader},
+    PgnError,
+};
+
+const GAMES: &str = r#"[Event "Casual game"]
+[White "Anderssen, Adolf"]
+[Black "Kieseritzky, Lionel"]
+[Result "1-0"]
+
+1. e4 e5 2. f4 exf4 {the King's Gambit} 3. Bc4 Qh4+ 4. Kf1 b5?! $6
+(4... Nf6 5. Nf3) 5. Bxb5 Nf6 6. Nf3 Qh6 7. d3 Nh5 8. Nh4 Qg5
+9. Nf5 c6 10. g4 Nf6 11. Rg1 cxb5 12. h4 Qg6 13. h5 Qg5 14. Qf3 Ng8
+15. Bxf4 Qf6 16. Nc3 Bc5 17. Nd5 Qxb2 18. Bd6 Bxg1 19. e5 Qxa1+ 20. Ke2 Na6
+21. Nxg7+ Kd8 22. Qf6+ Nxf6 23. Be7# 1-0
+
+[Event "Endgame study"]
+[SetUp "1"]
+[FEN "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1"]
+
+1. e4 ; a line comment
+Kd7 2. e5 Ke6 *
+
+[Event "Broken"]
+
+1. e4 e5 2. Nf3 Nf3 3. Bc4 1/2-1/2
+
+% an escaped line
+1.d4 d5 0-1
+"#;
+
+#[test]
+fn replays_games() {
+    let results: Vec<_> = PgnReader::new(GAMES).collect();
+
+    let positions: Vec<_> = results.iter().filter_map(|res| res.as_ref().ok()).collect();
+    let count = |game| positions.iter().filter(|(idx, ..)| *idx == game).count();
+    assert_eq!([count(0), count(1), count(2), count(3)], [46, 5, 4, 3]);
+
+    let (_, ply, mate) = positions[45];
+    assert_eq!(*ply, 45);
+    assert_eq!(
+        mate.to_fen(),
+        "r1bk3r/p2pBpNp/n4n2/1p1NP2P/6P1/3P4/P1P1K3/q5b1 b - - 1 23"
+    );
+
+    let (_, _, study) = positions[50];
+    assert_eq!(study.to_fen(), "8/8/4k3/4P3/8/8/8/4K3 w - - 1 3");
+
+    let errors: Vec<&PgnError> = results
+        .iter()
+        .filter_map(|res| res.as_ref().err())
+        .collect();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].game, 2);
+    assert_eq!(errors[0].ply, 4);
+    assert_eq!(errors[0].kind, PgnErrorKind::IllegalMove("Nf3".into()));
+    assert_eq!(errors[0].to_string(), "game 3, ply 4: illegal move 'Nf3'");
+}
+
+#[test]
+fn reports_bad_input() {
+    let err = |pgn| PgnReader::new(pgn).find_map(Result::err).unwrap();
+
+    let bad_fen = err("[FEN \"8/8/8 w - -\"]\n\n*");
+    assert_eq!((bad_fen.game, bad_fen.ply), (0, 0));
+    assert!(matches!(bad_fen.kind, PgnErrorKind::Fen(_)));
+
+    let unclosed = err("1. e4 {never closed 1-0");
+    assert_eq!((unclosed.game, unclosed.ply), (0, 1));
+    assert_eq!(unclosed.kind, PgnErrorKind::Unterminated("comment"));
+
+    assert_eq!(PgnReader::new("1. e4 {never closed 1-0").count(), 3);
+}
@@ -0,0 +1,121 @@
+use perft::{FenError, Position, Side, Status, STARTPOS};
+
+#[test]
+fn fen_errors() {
+    let cases = [
+        ("", FenError::MissingField("board")),
+        ("8/8/8/8/8/8/8 w - - 0 1", FenError::RankCount(7)),
+        ("4k3/8/8/8/8/8/8/4K3R w - -", FenError::RankOverflow(1)),
+        ("4k3/8/8/8/8/8/8/4K2 w - -", FenError::RankUnderflow(1)),
+        ("4k3/8/8/8/8/8/8/4X3 w - -", FenError::BadPiece('X')),
+        (
+            "4k3/8/8/8/8/8/8/8 w - -",
+            FenError::KingCount {
+                side: Side::WHITE,
+                count: 0,
+            },
+        ),
+        (
+            "4k3/8/8/8/8/8/8/4K3",
+            FenError::MissingField("side to move"),
+        ),
+        (
+            "4k3/8/8/8/8/8/PPPPPPPP/QQ2K3 w - -",
+            FenError::TooManyPieces(Side::WHITE),
+        ),
+        (
+            "1nnnk3/pppppppp/8/8/8/8/8/4K3 w - -",
+            FenError::TooManyPieces(Side::BLACK),
+        ),
+        (
+            "4k3/8/8/8/8/8/8/4K3 x - -",
+            FenError::BadSideToMove("x".into()),
+        ),
+        ("4k3/8/8/8/8/8/8/4K3 w X -", FenError::BadCastling('X')),
+        ("4k3/8/8/8/8/8/8/4K3 w K -", FenError::BadCastling('K')),
+        ("4k3/8/8/8/8/8/8/R3K3 w B -", FenError::BadCastling('B')),
+        (
+            "4k3/8/8/8/8/8/8/4K3 w - e6",
+            FenError::BadEnPassant("e6".into()),
+        ),
+        (
+            "4k3/8/8/8/8/8/8/4K3 w - b1",
+            FenError::BadEnPassant("b1".into()),
+        ),
+        ("4k3/4R3/8/8/8/8/8/4K3 w - -", FenError::OpponentInCheck),
+        (
+            "4k3/8/8/8/8/8/8/4K3 w - - x 1",
+            FenError::BadMoveClock("x".into()),
+        ),
+    ];
+
+    for (fen, err) in cases {
+        assert_eq!(Position::from_fen(fen).err(), Some(err), "{fen}");
+    }
+}
+
+// cutting a FEN short or swapping a character for another must give an
+// error or a position, never a panic
+#[test]
+fn malformed_fens_never_panic() {
+    let fens = [
+        STARTPOS,
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "rnbqkbnr/ppp1pppp/8/8/3pP3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 3",
+        "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+    ];
+    let swaps = "Kkp1/8 -wbe3h6";
+
+    for fen in fens {
+        for end in 0..fen.len() {
+            let _ = Position::from_fen(&fen[..end]);
+        }
+        for idx in 0..fen.len() {
+            for ch in swaps.chars() {
+                let mut swapped = fen.to_string();
+                swapped.replace_range(idx..=idx, &ch.to_string());
+                let _ = Position::from_fen(&swapped);
+            }
+        }
+    }
+}
+
+#[test]
+fn transpositions_share_hash() {
+    let mut a = Position::parse_fen(STARTPOS);
+    let mut b = a;
+    a.play_uci_moves("g1f3 g8f6 b1c3 b8c6").unwrap();
+    b.play_uci_moves("b1c3 b8c6 g1f3 g8f6").unwrap();
+    assert_eq!(a.hash(), b.hash());
+
+    // same pieces, different castling rights
+    b.play_uci_moves("a1b1 a8b8 b1a1 b8a8").unwrap();
+    assert_ne!(a.hash(), b.hash());
+    assert_eq!(b.hash(), b.compute_hash());
+}
+
+#[test]
+fn game_status() {
+    let cases = [
+        (STARTPOS, Status::Ongoing),
+        ("R5k1/5ppp/8/8/8/8/8/6K1 b - - 1 1", Status::Checkmate),
+        ("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1", Status::Stalemate),
+        (
+            "8/8/4k3/8/8/3NK3/8/8 w - - 0 1",
+            Status::InsufficientMaterial,
+        ),
+        (
+            "8/8/2b1k3/8/B7/3BK3/8/8 w - - 0 1",
+            Status::InsufficientMaterial,
+        ),
+        ("8/8/3bk3/8/8/3BK3/8/8 w - - 0 1", Status::Ongoing),
+        ("8/8/4k3/8/8/2NNK3/8/8 w - - 0 1", Status::Ongoing),
+        ("8/8/4k3/8/8/3RK3/8/8 w - - 99 80", Status::Ongoing),
+        ("8/8/4k3/8/8/3RK3/8/8 w - - 100 80", Status::FiftyMoveRule),
+        ("R5k1/5ppp/8/8/8/8/8/6K1 b - - 100 80", Status::Checkmate),
+    ];
+
+    for (fen, status) in cases {
+        assert_eq!(Position::parse_fen(fen).status(), status, "{fen}");
+    }
+}
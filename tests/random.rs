@@ -0,0 +1,88 @@
+use perft::{
+    perft,
+    random::{to_epd, RandomPositions, RandomSettings},
+    reference, Flag, Position, STARTPOS,
+};
+
+fn positions(seed: u64, settings: RandomSettings, count: usize) -> Vec<Position> {
+    let start = Position::parse_fen(STARTPOS);
+    RandomPositions::new(start, seed, settings)
+        .take(count)
+        .collect()
+}
+
+#[test]
+fn same_seed_same_positions() {
+    let fens = |seed| {
+        positions(seed, RandomSettings::default(), 20)
+            .iter()
+            .map(Position::to_fen)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(fens(5), fens(5));
+    assert_ne!(fens(5), fens(6));
+}
+
+#[test]
+fn settings_are_respected() {
+    let settings = RandomSettings {
+        min_ply: 20,
+        max_ply: 60,
+        min_pieces: 10,
+        max_pieces: 24,
+        en_passant: 100,
+        promotion: 0,
+        castling: 0,
+    };
+
+    for pos in positions(3, settings, 20) {
+        let plies = 2 * (pos.fullmove_number() - 1) + pos.stm() as u16;
+        assert!((20..=60).contains(&plies), "{pos}");
+        assert!((10..=24).contains(&pos.occ().count_ones()), "{pos}");
+
+        let moves = pos.gen();
+        let moves = &moves.list[..moves.len];
+        assert!(moves.iter().any(|mov| mov.flag() == Flag::ENP), "{pos}");
+    }
+
+    let settings = RandomSettings {
+        en_passant: 0,
+        promotion: 100,
+        castling: 100,
+        ..RandomSettings::default()
+    };
+    for pos in positions(4, settings, 10) {
+        let moves = pos.gen();
+        let moves = &moves.list[..moves.len];
+        assert!(moves.iter().any(|mov| mov.flag() & Flag::NPR > 0), "{pos}");
+        assert!(moves.iter().any(|mov| mov.is_castle()), "{pos}");
+    }
+}
+
+#[test]
+fn epd_lines_match_reference() {
+    for pos in positions(9, RandomSettings::default(), 30) {
+        let line = to_epd(&pos, 2);
+        let mut fields = line.split(';');
+        assert_eq!(fields.next(), Some(pos.to_fen().as_str()));
+
+        for (d, field) in fields.enumerate() {
+            let depth = d as u8 + 1;
+            let expected = perft::<false, true>(&pos, depth);
+            assert_eq!(field, format!(" D{depth} {expected}"));
+            assert_eq!(reference::perft(&pos, depth), expected, "{pos}");
+        }
+    }
+}
+
+#[test]
+fn unmeetable_settings_end() {
+    // no castling move is legal in the start position itself
+    let settings = RandomSettings {
+        min_ply: 0,
+        max_ply: 0,
+        castling: 100,
+        ..RandomSettings::default()
+    };
+    assert!(positions(1, settings, 1).is_empty());
+}
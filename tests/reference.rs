@@ -0,0 +1,68 @@
+use perft::{reference::legal_moves, Flag, Move, Position};
+
+fn key(mov: &Move) -> (u8, u8, u8, usize) {
+    (mov.from(), mov.to(), mov.flag(), mov.moved())
+}
+
+fn assert_same_moves(pos: &Position) {
+    let moves = pos.gen();
+    let mut fast = moves.list[..moves.len].iter().map(key).collect::<Vec<_>>();
+    let mut slow = legal_moves(pos).iter().map(key).collect::<Vec<_>>();
+    fast.sort_unstable();
+    slow.sort_unstable();
+    assert_eq!(fast, slow, "{pos}");
+}
+
+// every position up to `depth` plies from each line of the suite
+fn check_suite(epd: &str, depth: u8) {
+    fn walk(pos: &Position, depth: u8) {
+        assert_same_moves(pos);
+        if depth > 0 {
+            let moves = pos.gen();
+            for &mov in &moves.list[..moves.len] {
+                let mut tmp = *pos;
+                tmp.make(mov);
+                walk(&tmp, depth - 1);
+            }
+        }
+    }
+
+    for line in epd.lines() {
+        let fen = line.split(';').next().unwrap();
+        walk(&Position::parse_fen(fen), depth);
+    }
+}
+
+#[test]
+fn reference_matches_suite() {
+    check_suite(include_str!("../perft_results.txt"), 2);
+}
+
+#[test]
+fn reference_matches_chess960_suite() {
+    check_suite(include_str!("../perft_960.txt"), 1);
+}
+
+#[test]
+fn en_passant_legality() {
+    // (position, whether the capture is legal)
+    let cases = [
+        ("4k3/8/8/2pP4/8/8/8/4K3 w - c6 0 1", true),
+        // taking opens the rank between the rook and the king
+        ("4k3/8/8/1rpP2K1/8/8/8/8 w - c6 0 1", false),
+        // taking opens the diagonal the taken pawn was blocking
+        ("4k3/8/1b6/2pP4/8/4K3/8/8 w - c6 0 1", false),
+        // taking removes the pawn giving check
+        ("8/8/8/2pP4/3K4/8/8/4k3 w - c6 0 1", true),
+        // the taking pawn is pinned along its file
+        ("2r1k3/8/8/1pP5/8/8/8/2K5 w - b6 0 1", false),
+        ("3k4/8/8/8/3pP3/8/8/3R2K1 b - e3 0 1", false),
+    ];
+
+    for (fen, legal) in cases {
+        let pos = Position::parse_fen(fen);
+        assert_same_moves(&pos);
+        let enp = legal_moves(&pos).iter().any(|mov| mov.flag() == Flag::ENP);
+        assert_eq!(enp, legal, "{fen}");
+    }
+}
@@ -0,0 +1,78 @@
+use perft::{Position, STARTPOS};
+
+fn san(fen: &str, uci: &str) -> String {
+    let pos = Position::parse_fen(fen);
+    pos.to_san(pos.parse_uci_move(uci).unwrap())
+}
+
+#[test]
+fn writes_san() {
+    let cases = [
+        (STARTPOS, "g1f3", "Nf3"),
+        (STARTPOS, "e2e4", "e4"),
+        ("4k3/8/8/8/8/5N2/8/1N2K3 w - - 0 1", "b1d2", "Nbd2"),
+        ("4k3/8/8/R7/8/8/8/R3K3 w - - 0 1", "a1a3", "R1a3"),
+        ("4k3/8/8/8/8/Q7/8/Q1Q1K3 w - - 0 1", "a1b2", "Qa1b2"),
+        ("4k3/8/8/3p4/4P3/8/8/4K3 w - - 0 1", "e4d5", "exd5"),
+        ("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1", "b7b8q", "b8=Q+"),
+        ("r3k3/1P6/8/8/8/8/8/4K3 w - - 0 1", "b7a8n", "bxa8=N"),
+        ("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", "a1a8", "Ra8#"),
+        ("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", "e1g1", "O-O"),
+        ("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", "e1c1", "O-O-O"),
+        ("r3k1r1/8/8/8/8/8/8/RK5R w HAga - 0 1", "b1a1", "O-O-O"),
+        ("r3k1r1/8/8/8/8/8/8/RK5R w HAga - 0 1", "b1c1", "Kc1"),
+    ];
+
+    for (fen, uci, expected) in cases {
+        assert_eq!(san(fen, uci), expected, "{fen}");
+    }
+}
+
+#[test]
+fn parses_san() {
+    let pos = Position::parse_fen("4k3/8/8/8/8/5N2/8/1N2K3 w - - 0 1");
+    assert!(pos.parse_san("Nd2").is_none());
+    assert!(pos.parse_san("Nxd2").is_none());
+    assert_eq!(pos.parse_san("Nbd2").unwrap().to_uci(false), "b1d2");
+    assert_eq!(pos.parse_san("Nb1d2").unwrap().to_uci(false), "b1d2");
+    assert_eq!(pos.parse_san("Nfd2!?").unwrap().to_uci(false), "f3d2");
+
+    let pos = Position::parse_fen("r3k3/1P6/8/8/8/8/8/4K3 w - - 0 1");
+    assert_eq!(pos.parse_san("bxa8=Q+").unwrap().to_uci(false), "b7a8q");
+    assert_eq!(pos.parse_san("b8R").unwrap().to_uci(false), "b7b8r");
+    assert!(pos.parse_san("b8").is_none());
+
+    let pos = Position::parse_fen("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1");
+    assert_eq!(pos.parse_san("0-0-0").unwrap().to_uci(false), "e8c8");
+    assert!(pos.parse_san("O-O-O-O").is_none());
+
+    // pawn captures need the file they're made from
+    let pos = Position::parse_fen("4k3/8/8/3p4/4P3/8/8/4K3 w - - 0 1");
+    assert!(pos.parse_san("d5").is_none());
+    assert!(pos.parse_san("xd5").is_none());
+    assert_eq!(pos.parse_san("exd5").unwrap().to_uci(false), "e4d5");
+    assert_eq!(pos.parse_san("e5").unwrap().to_uci(false), "e4e5");
+
+    let pos = Position::parse_fen("4k3/8/8/2pP4/8/8/8/4K3 w - c6 0 1");
+    assert!(pos.parse_san("c6").is_none());
+    assert_eq!(pos.parse_san("dxc6").unwrap().to_uci(false), "d5c6");
+}
+
+#[test]
+fn round_trip() {
+    let suites = [
+        include_str!("../perft_results.txt"),
+        include_str!("../perft_960.txt"),
+    ];
+    for line in suites.iter().flat_map(|suite| suite.lines()) {
+        let pos = Position::parse_fen(line.split(';').next().unwrap());
+        let moves = pos.gen();
+        for &mov in &moves.list[..moves.len] {
+            let san = pos.to_san(mov);
+            let parsed = pos
+                .parse_san(&san)
+                .unwrap_or_else(|| panic!("{san} in {pos}"));
+            assert_eq!(parsed.to_uci(true), mov.to_uci(true), "{san} in {pos}");
+        }
+    }
+}